@@ -0,0 +1,499 @@
+use clap::{Arg, App, SubCommand, AppSettings, ArgGroup};
+use nptt::db::db_manager::AbsenceCategory;
+use nptt::holidays;
+use nptt::parsing_utils::validators;
+use nptt::period;
+use nptt::profiles;
+use nptt::report_generation::{GroupBy, OutputFormat, TimeFormat};
+
+/// Options for the minutes of work per weekday in a daily time override, starting with Monday.
+pub const WEEKDAY_OPTIONS: [(&str, &str); 7] = [("mon", "Minutes of work on Mondays."), ("tue", "Minutes of work on Tuesdays."),
+    ("wed", "Minutes of work on Wednesdays."), ("thu", "Minutes of work on Thursdays."), ("fri", "Minutes of work on Fridays."),
+    ("sat", "Minutes of work on Saturdays."), ("sun", "Minutes of work on Sundays.")];
+
+/// --period selects a named period instead of -s and -e.
+fn period_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("period")
+        .long("period")
+        .takes_value(true)
+        .conflicts_with_all(&["start-day", "end-day"])
+        .validator(period::validator)
+        .help("The period instead of -s and -e. Can be one of 'this-week', 'last-week', 'this-month', 'last-month', 'this-year', 'last-year', 'ytd' (year to date), a year (2024), a month (2024-03), a week (2024-W12) or a quarter (2024-Q2).")
+}
+
+pub fn get_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("No Project Time Tracker")
+        .version("0.2.1")
+        .author("Simon Lindhén; Github: SiXoS")
+        .about("Track your time in a comfortable environment without silly buttons and pictures! Change DB location with environment variable NPTT_DB_LOCATION. Defaults are read from ~/.config/nptt/config.toml, or the file in NPTT_CONFIG.")
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .arg(Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .global(true)
+            .possible_values(&OutputFormat::NAMES)
+            .help("Output format of list-time, list-flex, list-absence, list-holidays, list-daily-time-override and report. 'json' gives one JSON document, see the README for the schema. Defaults to output_format of the config file, or 'human'."))
+        .arg(Arg::with_name("time-format")
+            .long("time-format")
+            .takes_value(true)
+            .global(true)
+            .possible_values(&TimeFormat::NAMES)
+            .help("Show times with 24 hours, 17:30, or 12 hours, 5:30 PM. Defaults to time_format of the config file, or '24h'."))
+        .arg(Arg::with_name("profile")
+            .long("profile")
+            .takes_value(true)
+            .global(true)
+            .validator(profiles::name_validator)
+            .help("The profile to use instead of the one selected with 'profile switch'. Each profile has its own entries and config."))
+        .subcommand(SubCommand::with_name("add-time")
+            .about("Add a new line in time tracking.")
+            .arg(Arg::with_name("start")
+                .required(true)
+                .index(1)
+                .validator(validators::time_validator)
+                .help("Time to start the line. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("end")
+                .required(true)
+                .index(2)
+                .validator(validators::time_validator)
+                .help("Time to end the line. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("day")
+                .required(true)
+                .index(3)
+                .validator(validators::day_validator)
+                .help("The day that the time should be recorded for. Applies for both start and end unless -e is specified. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("end-day")
+                .short("e")
+                .long("end-day")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("The day of the end, for lines that span midnight. Without it an end before the start is on the next day. Same values as day."))
+            .arg(Arg::with_name("break-time")
+                .short("b")
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help("Break time you took (lunch mostly), in minutes or as a duration like 1h or 0:45. Defaults to default_break of the config file.")))
+        .subcommand(SubCommand::with_name("start")
+            .about("Start a timer. Stop it with the stop command to turn it into a time tracking line.")
+            .arg(Arg::with_name("at")
+                .long("at")
+                .takes_value(true)
+                .validator(validators::time_validator)
+                .help("When you started today, if not now. 24h format HH:mm or 12h format like 9:30am or 5pm")))
+        .subcommand(SubCommand::with_name("stop")
+            .about("Stop the running timer and add it as a time tracking line.")
+            .arg(Arg::with_name("at")
+                .long("at")
+                .takes_value(true)
+                .validator(validators::time_validator)
+                .help("When you stopped today, if not now. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("break-time")
+                .short("b")
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help("Break time you took while the timer was running, in minutes or as a duration like 1h or 0:45. Defaults to default_break of the config file.")))
+        .subcommand(SubCommand::with_name("status")
+            .about("Show for how long the timer has been running and how much flex you would get for the day if you stopped it now.")
+            .arg(Arg::with_name("break-time")
+                .short("b")
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help("Break time you took while the timer was running, in minutes or as a duration like 1h or 0:45. Defaults to default_break of the config file.")))
+        .subcommand(SubCommand::with_name("leave-at")
+            .about("When can I go home? Shows when today's flex reaches zero and when your total flex balance reaches zero (or a target). Counts from the running timer, --start or the end of today's last time line.")
+            .arg(Arg::with_name("break-time")
+                .short("b")
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help("Break time you expect to take today in total, in minutes or as a duration like 1h or 0:45. Breaks already reported today are subtracted. Defaults to default_break of the config file."))
+            .arg(Arg::with_name("start")
+                .long("start")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::time_validator)
+                .help("When you started working today, if there is no running timer. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("target")
+                .long("target")
+                .short("t")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validators::signed_decimal_validator)
+                .help("Total flex balance in hours you want to have when you leave. Defaults to 0.")))
+        .subcommand(SubCommand::with_name("smart-add")
+            .about("Will allow you to interactively add time for the previous workday(s) that has no time reported. This can be placed in your .bashrc for example. You will then be requested to add the time for unreported days as soon as you open the terminal. Will not do anything if the previous workday has a report.")
+            .arg(Arg::with_name("default start")
+                .long("start")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::time_validator)
+                .help("Default start time of the day. If this is specified it will be presented as an option during the interactive time report. Defaults to default_start of the config file. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("default end")
+                .long("end")
+                .short("e")
+                .takes_value(true)
+                .validator(validators::time_validator)
+                .help("Default end time of the day. If this is specified it will be presented as an option during the interactive time report. Defaults to default_end of the config file. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("default break time")
+                .long("break")
+                .short("b")
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help("Default break time in minutes or as a duration like 1h or 0:45. If this is specified it will be presented as an option during the interactive time report. Defaults to default_break of the config file.")))
+        .subcommand(SubCommand::with_name("list-time")
+            .about("List time tracking lines. Shows current month by default.")
+            .arg(Arg::with_name("start-day")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("From which day to list rows. Requires -e. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("end-day")
+                .short("e")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("To which day to list rows. Requires -s. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(period_arg()))
+        .subcommand(SubCommand::with_name("edit-time")
+            .about("Edit an existing time tracking line. Use list-time to find the id of the line. Values that are not specified are kept as they are.")
+            .arg(Arg::with_name("id")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::unsigned_number_validator)
+                .help("Id of the line to edit."))
+            .arg(Arg::with_name("start")
+                .long("start")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::time_validator)
+                .help("New start time of the line. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("end")
+                .long("end")
+                .short("e")
+                .takes_value(true)
+                .validator(validators::time_validator)
+                .help("New end time of the line. 24h format HH:mm or 12h format like 9:30am or 5pm"))
+            .arg(Arg::with_name("day")
+                .long("day")
+                .short("d")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("New day that the line should be recorded for. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("break-time")
+                .short("b")
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help("New break time, in minutes or as a duration like 1h or 0:45.")))
+        .subcommand(SubCommand::with_name("delete-time")
+            .about("Delete a time tracking line. Use list-time to find the id of the line.")
+            .arg(Arg::with_name("id")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::unsigned_number_validator)
+                .help("Id of the line to delete."))
+            .arg(Arg::with_name("yes")
+                .long("yes")
+                .short("y")
+                .help("Do not ask for confirmation before deleting.")))
+        .subcommand(SubCommand::with_name("report")
+            .about("Get a time report.")
+            .arg(Arg::with_name("start-day")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("From which day to list rows. Requires -e. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("end-day")
+                .short("e")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("To which day to list rows. Requires -s. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(period_arg())
+            .arg(Arg::with_name("csv")
+                .short("c")
+                .conflicts_with("all-profiles")
+                .help("Generates a csv report to stdout."))
+            .arg(Arg::with_name("group-by")
+                .long("group-by")
+                .takes_value(true)
+                .possible_values(&GroupBy::NAMES)
+                .conflicts_with("all-profiles")
+                .help("Show the worked, expected and flex hours per week, month or year instead of every day."))
+            .arg(Arg::with_name("all-profiles")
+                .long("all-profiles")
+                .help("Report the hours worked and the flex of every profile and the total across profiles.")))
+        .subcommand(SubCommand::with_name("add-flex")
+            .about("Add additional flex for occasions that don't coincide with normal condition. For example if you get double flex")
+            .setting(AppSettings::AllowLeadingHyphen)
+            .arg(Arg::with_name("flex-minutes")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::duration_validator)
+                .help("How much flex you want to add, in minutes or as a duration like 1h30m, 1.5h or 1:30. Use negative value to take from flex, e.g. -2h"))
+            .arg(Arg::with_name("date")
+                .takes_value(true)
+                .required(true)
+                .index(2)
+                .validator(validators::day_validator)
+                .help("Which date to register the flex time so that it gets in the correct report"))
+            .arg(Arg::with_name("comment")
+                .takes_value(true)
+                .short("c")
+                .help("Why did you add this line? So you can remember later on.")))
+        .subcommand(SubCommand::with_name("list-flex")
+            .about("List flex lines. Shows current month by default.")
+            .arg(Arg::with_name("start-day")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("From which day to list rows. Requires -e. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("end-day")
+                .short("e")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("To which day to list rows. Requires -s. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(period_arg()))
+        .subcommand(SubCommand::with_name("delete-flex")
+            .about("Delete a flex line. Use list-flex to find the id of the line.")
+            .arg(Arg::with_name("id")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::unsigned_number_validator)
+                .help("Id of the flex line to delete."))
+            .arg(Arg::with_name("yes")
+                .long("yes")
+                .short("y")
+                .help("Do not ask for confirmation before deleting.")))
+        .subcommand(SubCommand::with_name("add-absence")
+            .about("Register absence such as vacation or sick leave. Absence counts as worked time so it does not affect the flex bank. Start and end date are inclusive.")
+            .arg(Arg::with_name("category")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .possible_values(&AbsenceCategory::NAMES)
+                .help("What kind of absence this is."))
+            .arg(Arg::with_name("start-date")
+                .takes_value(true)
+                .required(true)
+                .index(2)
+                .validator(validators::day_validator)
+                .help("First day of the absence. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("end-date")
+                .takes_value(true)
+                .index(3)
+                .validator(validators::day_validator)
+                .help("Last day of the absence. Defaults to the start date. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("minutes")
+                .long("minutes")
+                .short("m")
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help("Absence per day for partial days, in minutes or as a duration like 4h. The absence covers whole days if this is not specified."))
+            .arg(Arg::with_name("comment")
+                .takes_value(true)
+                .short("c")
+                .help("Why were you absent? So you can remember later on.")))
+        .subcommand(SubCommand::with_name("list-absence")
+            .about("List absence. Shows current month by default.")
+            .arg(Arg::with_name("start-day")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("From which day to list rows. Requires -e. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("end-day")
+                .short("e")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("To which day to list rows. Requires -s. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(period_arg()))
+        .subcommand(SubCommand::with_name("delete-absence")
+            .about("Delete absence. Use list-absence to find the id of the absence.")
+            .arg(Arg::with_name("id")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::unsigned_number_validator)
+                .help("Id of the absence to delete."))
+            .arg(Arg::with_name("yes")
+                .long("yes")
+                .short("y")
+                .help("Do not ask for confirmation before deleting.")))
+        .subcommand(SubCommand::with_name("set-absence-allowance")
+            .about("Set how many days of absence you are entitled to in a year, e.g. 25 days of vacation. Reports show how many days you have left.")
+            .arg(Arg::with_name("category")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .possible_values(&AbsenceCategory::NAMES)
+                .help("What kind of absence the allowance is for."))
+            .arg(Arg::with_name("days")
+                .takes_value(true)
+                .required(true)
+                .index(2)
+                .validator(validators::unsigned_decimal_validator)
+                .help("Number of days, e.g. 25 or 27.5"))
+            .arg(Arg::with_name("year")
+                .long("year")
+                .takes_value(true)
+                .validator(validators::unsigned_number_validator)
+                .help("Which year the allowance is for. Defaults to the current year.")))
+        .subcommand(SubCommand::with_name("export-all")
+            .about("Export all data to a versioned JSON document that can be restored with import-all, e.g. on another computer.")
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .help("File to write the export to. Prints it if not specified.")))
+        .subcommand(SubCommand::with_name("import-all")
+            .about("Restore data from a document created with export-all. By default the data is merged with the existing data \
+                    and entries that already exist are skipped. Nothing is imported if anything fails.")
+            .arg(Arg::with_name("file")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .help("Path to the exported document."))
+            .arg(Arg::with_name("replace")
+                .long("replace")
+                .help("Delete all existing data before importing."))
+            .arg(Arg::with_name("yes")
+                .long("yes")
+                .short("y")
+                .help("Do not ask for confirmation before deleting the existing data with --replace.")))
+        .subcommand(SubCommand::with_name("import-csv")
+            .about("Import time entries and flex entries from a CSV file. The file can either have the layout that 'report -c' produces \
+                    or one entry per row with the header 'Type,Date,Start,End,Break,Minutes,Comment', where Type is 'time' or 'flex'. \
+                    Nothing is imported if any row is invalid.")
+            .arg(Arg::with_name("file")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .help("Path to the CSV file."))
+            .arg(Arg::with_name("dry-run")
+                .long("dry-run")
+                .help("Only validate the file and show what would be imported.")))
+        .subcommand(SubCommand::with_name("import-holidays")
+            .about("Import public holidays, either from the built in rules for a country or from an iCalendar (.ics) file. Holidays are expected to have no work and are skipped by smart-add. Dates that already have a holiday are kept as they are.")
+            .arg(Arg::with_name("country")
+                .long("country")
+                .takes_value(true)
+                .possible_values(&holidays::COUNTRIES)
+                .help("Country code to generate the holidays for."))
+            .arg(Arg::with_name("year")
+                .long("year")
+                .takes_value(true)
+                .requires("country")
+                .validator(validators::unsigned_number_validator)
+                .help("Which year to generate the holidays for. Defaults to the current year."))
+            .arg(Arg::with_name("ics")
+                .long("ics")
+                .takes_value(true)
+                .help("Path to an iCalendar file. Every event in the file is imported as a holiday."))
+            .group(ArgGroup::with_name("source")
+                .args(&["country", "ics"])
+                .required(true)))
+        .subcommand(SubCommand::with_name("add-holiday")
+            .about("Add a single holiday.")
+            .arg(Arg::with_name("date")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::day_validator)
+                .help("The day of the holiday. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("name")
+                .takes_value(true)
+                .required(true)
+                .index(2)
+                .help("Name of the holiday.")))
+        .subcommand(SubCommand::with_name("list-holidays")
+            .about("List holidays. Shows current year by default.")
+            .arg(Arg::with_name("start-day")
+                .short("s")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("From which day to list rows. Requires -e. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(Arg::with_name("end-day")
+                .short("e")
+                .takes_value(true)
+                .validator(validators::day_validator)
+                .help("To which day to list rows. Requires -s. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'"))
+            .arg(period_arg()))
+        .subcommand(SubCommand::with_name("delete-holiday")
+            .about("Delete the holiday on a date.")
+            .arg(Arg::with_name("date")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::day_validator)
+                .help("The day of the holiday. Can be e.g. 'today', 'yesterday', 'Xd' (X days ago), '+Xd' (in X days), 'friday' (the last one), 'last friday', 'this week', 'W12', 'MM-dd' or 'YYYY-MM-dd'")))
+        .subcommand(SubCommand::with_name("add-daily-time-override")
+            .about("Add daily time override (if you don't work 8 hours per day). Start date is inclusive. Daily minutes apply to the work days of the config file, \
+                    Monday to Friday by default. Use the weekday options for a schedule that differs between days, e.g. --fri 240 --wed 0. \
+                    Work days without a value get the daily minutes, or daily_minutes of the config file, and other days get no work.")
+            .arg(Arg::with_name("start-date")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::day_validator))
+            .arg(Arg::with_name("daily-minutes")
+                .takes_value(true)
+                .index(2)
+                .validator(validators::unsigned_duration_validator))
+            .args(&WEEKDAY_OPTIONS.iter().map(|(name, day)| Arg::with_name(name)
+                .long(name)
+                .takes_value(true)
+                .validator(validators::unsigned_duration_validator)
+                .help(day))
+                .collect::<Vec<Arg>>())
+            .group(ArgGroup::with_name("schedule")
+                .args(&["daily-minutes", "mon", "tue", "wed", "thu", "fri", "sat", "sun"])
+                .multiple(true)
+                .required(true)))
+        .subcommand(SubCommand::with_name("list-daily-time-override")
+            .about("List all daily time overrides."))
+        .subcommand(SubCommand::with_name("stop-daily-time-override")
+            .about("Set an end date for a daily time override. End date is exclusive")
+            .arg(Arg::with_name("id")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::unsigned_number_validator))
+            .arg(Arg::with_name("end-date")
+                .takes_value(true)
+                .required(true)
+                .index(2)
+                .validator(validators::day_validator)))
+        .subcommand(SubCommand::with_name("delete-daily-time-override")
+            .about("Delete a daily time override. Use list-daily-time-override to find the id of the override.")
+            .arg(Arg::with_name("id")
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .validator(validators::unsigned_number_validator)
+                .help("Id of the daily time override to delete."))
+            .arg(Arg::with_name("yes")
+                .long("yes")
+                .short("y")
+                .help("Do not ask for confirmation before deleting.")))
+        .subcommand(SubCommand::with_name("profile")
+            .about("Manage profiles, e.g. one per employer. Each profile has its own entries, flex, overrides and config.")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name("create")
+                .about("Create a new profile.")
+                .arg(Arg::with_name("name")
+                    .takes_value(true)
+                    .required(true)
+                    .index(1)
+                    .validator(profiles::name_validator)
+                    .help("Name of the profile. Letters, digits, '-' and '_'.")))
+            .subcommand(SubCommand::with_name("list")
+                .about("List all profiles and show which one is used."))
+            .subcommand(SubCommand::with_name("switch")
+                .about("Use another profile when --profile is not given.")
+                .arg(Arg::with_name("name")
+                    .takes_value(true)
+                    .required(true)
+                    .index(1)
+                    .validator(profiles::name_validator)
+                    .help("Name of the profile."))))
+}
@@ -0,0 +1,39 @@
+use crate::error::NpttError;
+use chrono::{Date, DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+/// The source of the current time. Everything that depends on today, e.g. `today`, `yesterday`, `smart-add` and the
+/// default periods of the list commands, asks the clock instead of the system so it can be fixed in tests and scripts.
+#[derive(Clone, Copy, Debug)]
+pub enum Clock {
+    System,
+    Fixed(DateTime<Local>)
+}
+
+impl Clock {
+    /// Parses a value of `NPTT_NOW`: an RFC 3339 time, `YYYY-MM-DD HH:mm` in local time or `YYYY-MM-DD` for midnight.
+    /// An empty value means the system time.
+    pub fn parse(now: &str) -> Result<Clock, NpttError> {
+        if now.is_empty() {
+            return Ok(Clock::System);
+        }
+        let invalid = || NpttError::Parse(format!("'{}' is not a valid time. Use RFC 3339, 'YYYY-MM-DD HH:mm' or 'YYYY-MM-DD'.", now));
+        if let Ok(time) = DateTime::parse_from_rfc3339(now) {
+            return Ok(Clock::Fixed(time.with_timezone(&Local)));
+        }
+        let local = NaiveDateTime::parse_from_str(now, "%Y-%m-%d %H:%M")
+            .or_else(|_| NaiveDate::parse_from_str(now, "%Y-%m-%d").map(|date| date.and_hms(0, 0, 0)))
+            .map_err(|_| invalid())?;
+        Local.from_local_datetime(&local).single().map(Clock::Fixed).ok_or_else(invalid)
+    }
+
+    pub fn now(&self) -> DateTime<Local> {
+        match self {
+            Clock::System => Local::now(),
+            Clock::Fixed(now) => *now
+        }
+    }
+
+    pub fn today(&self) -> Date<Local> {
+        self.now().date()
+    }
+}
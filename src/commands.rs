@@ -0,0 +1,657 @@
+use chrono::{DateTime, Local, Datelike, Date, NaiveDate, TimeZone, Duration};
+use clap::ArgMatches;
+use crate::app::WEEKDAY_OPTIONS;
+use nptt::{Clock, Config, NpttError, Tracker};
+use nptt::csv_import::{self, ImportEntry};
+use nptt::db::db_manager::AbsenceCategory;
+use nptt::dump::{Dump, ImportMode};
+use nptt::holidays;
+use nptt::parsing_utils::*;
+use nptt::period::{self, Period};
+use nptt::profiles::Profiles;
+use nptt::report_generation::*;
+use std::io::{self};
+use std::fs;
+use serde_json::{json, Value};
+
+/// Runs the profile commands and the combined report, which work on all profiles, and otherwise the command on the
+/// profile given with --profile or selected with 'profile switch'.
+pub fn execute_with_profiles(matches: ArgMatches, profiles: &Profiles) -> Result<Vec<String>, NpttError> {
+    let name = match matches.value_of("profile").or_else(|| matches.subcommand().1.and_then(|sub_matches| sub_matches.value_of("profile"))) {
+        Some(name) => name.to_string(),
+        None => profiles.current()?
+    };
+    match matches.subcommand() {
+        ("profile", Some(sub_matches)) => match sub_matches.subcommand() {
+            ("create", Some(profile_matches)) => create_profile(required_value(profile_matches, "name")?, profiles),
+            ("list", _) => list_profiles(name.as_str(), profiles),
+            ("switch", Some(profile_matches)) => switch_profile(required_value(profile_matches, "name")?, profiles),
+            (command, _) => Err(NpttError::Validation(format!("Command 'profile {}' is not implemented", command)))
+        },
+        ("report", Some(sub_matches)) if sub_matches.is_present("all-profiles") => {
+            let config = profiles.config(name.as_str())?;
+            let clock = profiles.clock();
+            let (start, end) = parse_period(sub_matches, clock)?.map_or_else(|| period::current_month(clock), Ok)?;
+            combined_report(start, end, output_format(&matches, &config), config.date_format.as_str(), profiles)
+        },
+        _ => {
+            let connection = profiles.open(name.as_str())?;
+            execute_commands(matches, &Tracker::new(&connection, profiles.config(name.as_str())?))
+        }
+    }
+}
+
+pub fn execute_commands(matches: ArgMatches, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let format = output_format(&matches, tracker.config());
+    let time_format = time_format(&matches, tracker.config());
+    let clock = tracker.clock();
+    let config = tracker.config();
+    match matches.subcommand() {
+        ("add-time", Some(sub_matches)) => {
+            let start = parsers::force_parse_datetime(sub_matches.value_of("start"), sub_matches.value_of("day"), clock)?;
+            add_line(start, end_of_line(sub_matches, &start, clock)?, break_time(sub_matches, config)?, time_format, tracker)
+        },
+        ("list-time", Some(sub_matches)) => {
+            let (start, end) = parse_period(sub_matches, clock)?.map_or_else(|| period::current_month(clock), Ok)?;
+            list_lines(start, end, format, time_format, tracker)
+        },
+        ("edit-time", Some(sub_matches)) => edit_line(parsers::force_parse_integer(sub_matches.value_of("id"))?,
+                                                      parsers::parse_time(sub_matches.value_of("start"))?,
+                                                      parsers::parse_time(sub_matches.value_of("end"))?,
+                                                      sub_matches.value_of("day").map(|day| parsers::get_date_from_string(day, clock)).transpose()?,
+                                                      parsers::parse_duration(sub_matches.value_of("break-time"))?,
+                                                      time_format,
+                                                      tracker),
+        ("delete-time", Some(sub_matches)) => delete_line(parsers::force_parse_integer(sub_matches.value_of("id"))?, sub_matches.is_present("yes"), time_format, tracker),
+        ("report", Some(sub_matches)) => {
+            let (start, end) = parse_period(sub_matches, clock)?.map_or_else(|| period::current_month(clock), Ok)?;
+            match sub_matches.value_of("group-by").and_then(GroupBy::from_name) {
+                Some(group_by) => summary_report(start, end, sub_matches.is_present("csv"), format, group_by, tracker),
+                None => report(start, end, sub_matches.is_present("csv"), format, time_format, tracker)
+            }
+        },
+        ("add-flex", Some(sub_matches)) => add_flex(parsers::force_parse_duration(sub_matches.value_of("flex-minutes"))?,
+                                                    parsers::force_parse_date(sub_matches.value_of("date"), clock)?,
+                                                    sub_matches.value_of("comment"),
+                                                    tracker),
+        ("list-flex", Some(sub_matches)) => {
+            let (start, end) = parse_period(sub_matches, clock)?.map_or_else(|| period::current_month(clock), Ok)?;
+            list_flex(start, end, format, tracker)
+        },
+        ("delete-flex", Some(sub_matches)) => delete_flex(parsers::force_parse_integer(sub_matches.value_of("id"))?, sub_matches.is_present("yes"), tracker),
+        ("start", Some(sub_matches)) => start_timer(parse_time_today(sub_matches.value_of("at"), clock)?.unwrap_or_else(|| tracker.current_minute()),
+                                                  time_format,
+                                                  tracker),
+        ("stop", Some(sub_matches)) => stop_timer(parse_time_today(sub_matches.value_of("at"), clock)?.unwrap_or_else(|| tracker.current_minute()),
+                                                break_time(sub_matches, config)?,
+                                                time_format,
+                                                tracker),
+        ("status", Some(sub_matches)) => timer_status(break_time(sub_matches, config)?, time_format, tracker),
+        ("leave-at", Some(sub_matches)) => leave_at(break_time(sub_matches, config)?,
+                                                    parse_time_today(sub_matches.value_of("start"), clock)?,
+                                                    parsers::force_parse_decimal(sub_matches.value_of("target"))?,
+                                                    time_format,
+                                                    tracker),
+        ("smart-add", Some(sub_matches)) => {
+            let default_break = config.default_break.map(|minutes| minutes.to_string());
+            smart_add(sub_matches.value_of("default start").or(config.default_start.as_deref()),
+                      sub_matches.value_of("default end").or(config.default_end.as_deref()),
+                      sub_matches.value_of("default break time").or(default_break.as_deref()), time_format, tracker)
+        },
+        ("add-absence", Some(sub_matches)) => add_absence(parsers::force_parse_absence_category(sub_matches.value_of("category"))?,
+                                                          parsers::force_parse_date(sub_matches.value_of("start-date"), clock)?,
+                                                          parsers::force_parse_date(sub_matches.value_of("end-date").or_else(|| sub_matches.value_of("start-date")), clock)?,
+                                                          parsers::parse_duration(sub_matches.value_of("minutes"))?,
+                                                          sub_matches.value_of("comment"),
+                                                          tracker),
+        ("list-absence", Some(sub_matches)) => {
+            let (start, end) = parse_period(sub_matches, clock)?.map_or_else(|| period::current_month(clock), Ok)?;
+            list_absence(start, end, format, tracker)
+        },
+        ("delete-absence", Some(sub_matches)) => delete_absence(parsers::force_parse_integer(sub_matches.value_of("id"))?, sub_matches.is_present("yes"), tracker),
+        ("set-absence-allowance", Some(sub_matches)) => set_absence_allowance(parsers::force_parse_absence_category(sub_matches.value_of("category"))?,
+                                                                              parsers::force_parse_decimal(sub_matches.value_of("days"))?,
+                                                                              parsers::parse_integer(sub_matches.value_of("year"))?.unwrap_or_else(|| clock.today().year()),
+                                                                              tracker),
+        ("export-all", Some(sub_matches)) => export_all(sub_matches.value_of("output"), tracker),
+        ("import-all", Some(sub_matches)) => import_all(required_value(sub_matches, "file")?,
+                                                        if sub_matches.is_present("replace") { ImportMode::Replace } else { ImportMode::Merge },
+                                                        sub_matches.is_present("yes"),
+                                                        tracker),
+        ("import-csv", Some(sub_matches)) => import_csv(required_value(sub_matches, "file")?, sub_matches.is_present("dry-run"), tracker),
+        ("import-holidays", Some(sub_matches)) => match sub_matches.value_of("ics") {
+            Some(path) => import_holidays_from_ics(path, tracker),
+            None => import_holidays_for_country(required_value(sub_matches, "country")?,
+                                                parsers::parse_integer(sub_matches.value_of("year"))?.unwrap_or_else(|| clock.today().year()),
+                                                tracker)
+        },
+        ("add-holiday", Some(sub_matches)) => add_holiday(parsers::force_parse_date(sub_matches.value_of("date"), clock)?, required_value(sub_matches, "name")?, tracker),
+        ("list-holidays", Some(sub_matches)) => {
+            let (start, end) = parse_period(sub_matches, clock)?.map_or_else(|| period::current_year(clock), Ok)?;
+            list_holidays(start, end, format, tracker)
+        },
+        ("delete-holiday", Some(sub_matches)) => delete_holiday(parsers::force_parse_date(sub_matches.value_of("date"), clock)?, tracker),
+        ("add-daily-time-override", Some(sub_matches)) => {
+            let daily_minutes = parsers::parse_duration(sub_matches.value_of("daily-minutes"))?.unwrap_or(config.daily_minutes);
+            let mut minutes_per_weekday = config.minutes_per_weekday(daily_minutes);
+            for (index, (name, _)) in WEEKDAY_OPTIONS.iter().enumerate() {
+                if let Some(minutes) = parsers::parse_duration(sub_matches.value_of(name))? {
+                    minutes_per_weekday[index] = minutes;
+                }
+            }
+            add_daily_time_override(parsers::force_parse_date(sub_matches.value_of("start-date"), clock)?, minutes_per_weekday, tracker)
+        },
+        ("list-daily-time-override", _) => list_daily_time_override(format, tracker),
+        ("stop-daily-time-override", Some(sub_matches)) => stop_daily_time_override(parsers::force_parse_integer(sub_matches.value_of("id"))?,
+                                                                                    parsers::force_parse_date(sub_matches.value_of("end-date"), clock)?, tracker),
+        ("delete-daily-time-override", Some(sub_matches)) => delete_daily_time_override(parsers::force_parse_integer(sub_matches.value_of("id"))?,
+                                                                                        sub_matches.is_present("yes"), tracker),
+        (command, _) => Err(NpttError::Validation(format!("Command '{}' is not implemented", command)))
+    }
+}
+
+/// The period of --period, or from -s to -e, both inclusive. Returns `None` if none of them is given.
+fn parse_period(sub_matches: &ArgMatches, clock: &Clock) -> Result<Option<Period>, NpttError> {
+    if let Some(named) = sub_matches.value_of("period") {
+        return period::parse(named, clock).map(Some);
+    }
+    match (sub_matches.value_of("start-day"), sub_matches.value_of("end-day")) {
+        (Some(start), Some(end)) => Ok(Some((parsers::get_date_from_string(start, clock)?.and_hms(0, 0, 0),
+                                             parsers::get_date_from_string(end, clock)?.succ().and_hms(0, 0, 0)))),
+        (None, Some(_)) => Err(NpttError::Validation("No -s flag specified with -e.".to_string())),
+        (Some(_), None) => Err(NpttError::Validation("No -e flag specified with -s.".to_string())),
+        (None, None) => Ok(None)
+    }
+}
+
+/// A time of day today, as given to start, stop and leave-at.
+fn parse_time_today(time: Option<&str>, clock: &Clock) -> Result<Option<DateTime<Local>>, NpttError> {
+    time.map(|time| parsers::force_parse_datetime(Some(time), Some("today"), clock)).transpose()
+}
+
+/// The end of an add-time line on the -e end day. Without it an end before the start is on the day after the start.
+fn end_of_line(sub_matches: &ArgMatches, start: &DateTime<Local>, clock: &Clock) -> Result<DateTime<Local>, NpttError> {
+    if sub_matches.is_present("end-day") {
+        return parsers::force_parse_datetime(sub_matches.value_of("end"), sub_matches.value_of("end-day"), clock);
+    }
+    let end = parsers::force_parse_datetime(sub_matches.value_of("end"), sub_matches.value_of("day"), clock)?;
+    Ok(if end < *start { end + Duration::days(1) } else { end })
+}
+
+/// The -b break time in minutes, or the default break of the config.
+fn break_time(sub_matches: &ArgMatches, config: &Config) -> Result<i32, NpttError> {
+    Ok(parsers::parse_duration(sub_matches.value_of("break-time"))?.or(config.default_break).unwrap_or(0))
+}
+
+fn required_value<'a>(sub_matches: &'a ArgMatches, name: &str) -> Result<&'a str, NpttError> {
+    sub_matches.value_of(name).ok_or_else(|| NpttError::Validation(format!("The argument '{}' is required.", name)))
+}
+
+fn smart_add(default_start: Option<&str>, default_end: Option<&str>, default_break: Option<&str>, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let dates_to_report = tracker.dates_to_report()?;
+    let mut lines = Vec::new();
+    if let Some(timer_start) = tracker.running_timer()? {
+        if timer_start.date() < tracker.clock().today() {
+            lines.push(format!("A timer has been running since {}. Don't forget to stop it.", timer_start.format(&tracker.date_time_format(time_format))));
+        }
+    }
+    if !dates_to_report.is_empty() {
+        for date in dates_to_report {
+            smart_add_date(date, default_start, default_end, default_break, time_format, tracker)?;
+        }
+        lines.push("Inserted time entries".to_string());
+    }
+    Ok(lines)
+}
+
+fn start_timer(start: DateTime<Local>, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.start_timer(&start, time_format)?;
+    Ok(vec![format!("Started timer at {}.", start.format(&tracker.date_time_format(time_format)))])
+}
+
+fn stop_timer(end: DateTime<Local>, break_time: i32, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let start = tracker.stop_timer(&end, break_time, time_format)?;
+    let date_time_format = tracker.date_time_format(time_format);
+    Ok(vec![format!("Stopped timer. Added line: from {} to {} with breaks of {} minutes.", start.format(&date_time_format), end.format(&date_time_format), break_time)])
+}
+
+fn timer_status(break_time: i32, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let status = match tracker.timer_status(break_time)? {
+        None => return Ok(vec!["No timer is running.".to_string()]),
+        Some(status) => status
+    };
+    let running_minutes = (status.now - status.start).num_minutes();
+    Ok(vec![
+        format!("Timer running since {} ({} hours and {} minutes).", status.start.format(&tracker.date_time_format(time_format)), running_minutes / 60, running_minutes % 60),
+        format!("Stopping now with breaks of {} minutes gives {} minutes of flex for {}.", break_time, status.flex_minutes, status.start.format(&tracker.config().date_format))
+    ])
+}
+
+fn leave_at(expected_break: i32, start: Option<DateTime<Local>>, target_flex_hours: f64, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let leave = tracker.leave_at(expected_break, start, target_flex_hours)?;
+    let date_time_format = tracker.date_time_format(time_format);
+    Ok(vec![
+        describe_leave_time(leave.zero_flex_today, &leave.now, "to get 0 flex for today".to_string(), time_format, &date_time_format),
+        describe_leave_time(leave.target_balance, &leave.now, format!("to get a total flex balance of {:.2} hours", target_flex_hours), time_format, &date_time_format)
+    ])
+}
+
+/// Only the time if the leave time is today, otherwise the date as well.
+fn describe_leave_time(leave: DateTime<Local>, now: &DateTime<Local>, goal: String, time_format: TimeFormat, date_time_format: &str) -> String {
+    let time = leave.format(if leave.date() == now.date() { time_format.strftime() } else { date_time_format }).to_string();
+    if leave < *now {
+        format!("You could have left at {} {}.", time, goal)
+    } else {
+        format!("Leave at {} {}.", time, goal)
+    }
+}
+
+fn smart_add_date(date: NaiveDate, default_start: Option<&str>, default_end: Option<&str>, default_break: Option<&str>, time_format: TimeFormat, tracker: &Tracker) -> Result<(), NpttError> {
+    let day = format!("{} {}", date.format("%A"), date.format(&tracker.config().date_format));
+    println!("Adding time for {}:", day);
+    let start = ask_with_optional_default("When did you start? Or type 'skip' to skip this day altogether.", default_start, |value| if value == "skip" {Ok(())} else { validators::time_validator(value) })?;
+    if start == "skip" {
+        println!("Ok, skipping.");
+        return Ok(());
+    }
+    let (start_h, start_m) = parsers::force_parse_time(start)?;
+    let (end_h, end_m) = parsers::force_parse_time(ask_with_optional_default("When did you go home?", default_end, validators::time_validator)?)?;
+    let break_minutes = parsers::force_parse_duration(Some(ask_with_optional_default("How much breaks, in minutes or as a duration like 1h, did you take?", default_break, validators::duration_validator)?.as_str()))?;
+    let start_date = Local.ymd(date.year(), date.month(), date.day()).and_hms(start_h, start_m, 0);
+    let minutes_in_day = tracker.expected_minutes(&date)? as i32;
+    let mut end_date = Local.ymd(date.year(), date.month(), date.day()).and_hms(end_h, end_m, 0);
+    if end_date < start_date {
+        end_date = end_date + Duration::days(1);
+    }
+    let flex = (end_date - start_date).num_minutes() as i32 - break_minutes - minutes_in_day;
+    let accepted = ask_with_optional_default(format!("Is this correct? {} from {} to {} with breaks of {} minutes which results in {} minutes of flex?", day,
+                                                     start_date.format(time_format.strftime()), end_date.format(time_format.strftime()), break_minutes, flex).as_str(),
+                                             Some("y"), |_| Ok(()))?;
+    if accepted == "y" || accepted == "Y" {
+        tracker.add_time(&start_date, &end_date, break_minutes)
+    } else {
+        println!("Alright, I'll ask again:");
+        smart_add_date(date, default_start, default_end, default_break, time_format, tracker)
+    }
+}
+
+fn ask_with_optional_default<F>(question: &str, default: Option<&str>, validator: F) -> Result<String, NpttError>
+    where
+        F: Fn(String) -> Result<(), String>
+{
+    println!("{}{}", question, default.map(|start| format!(" [{}]", start)).unwrap_or_default());
+    let mut buffer = String::new();
+    io::stdin().read_line(&mut buffer).map_err(NpttError::io("Could not read from stdin".to_string()))?;
+    match default {
+        None => {
+            let mut validation_result = validator(buffer.trim().to_string());
+            while buffer.trim().is_empty() || validation_result.is_err() {
+                println!("{}", if buffer.trim().is_empty() { "Please provide an answer.".to_string() } else { validation_result.unwrap_err() });
+                buffer.clear();
+                io::stdin().read_line(&mut buffer).map_err(NpttError::io("Could not read from stdin".to_string()))?;
+                validation_result = validator(buffer.trim().to_string());
+            }
+            Ok(buffer.trim().to_string())
+        }
+        Some(default_value) =>
+            if buffer.trim().is_empty() {
+                Ok(default_value.to_string())
+            } else {
+                let mut validation_result = validator(buffer.trim().to_string());
+                while validation_result.is_err() {
+                    println!("{}", validation_result.unwrap_err());
+                    buffer.clear();
+                    io::stdin().read_line(&mut buffer).map_err(NpttError::io("Could not read from stdin".to_string()))?;
+                    validation_result = validator(buffer.trim().to_string());
+                }
+                Ok(buffer.trim().to_string())
+            }
+    }
+}
+
+fn create_profile(name: &str, profiles: &Profiles) -> Result<Vec<String>, NpttError> {
+    profiles.create(name)?;
+    let mut lines = vec![format!("Created profile '{}'. Use it with --profile {} or make it the default with 'profile switch {}'.", name, name, name)];
+    if let Some(path) = profiles.config_path(name) {
+        lines.push(format!("Put the config of the profile in {}.", path.display()));
+    }
+    Ok(lines)
+}
+
+fn list_profiles(selected: &str, profiles: &Profiles) -> Result<Vec<String>, NpttError> {
+    Ok(profiles.list()?.into_iter()
+        .map(|name| if name == selected { format!("{} (in use)", name) } else { name })
+        .collect())
+}
+
+fn switch_profile(name: &str, profiles: &Profiles) -> Result<Vec<String>, NpttError> {
+    profiles.switch(name)?;
+    Ok(vec![format!("Switched to profile '{}'.", name)])
+}
+
+fn combined_report(start: DateTime<Local>, end: DateTime<Local>, format: OutputFormat, date_format: &str, profiles: &Profiles) -> Result<Vec<String>, NpttError> {
+    let mut summaries = Vec::new();
+    for name in profiles.list()? {
+        let connection = profiles.open(name.as_str())?;
+        let tracker = Tracker::new(&connection, profiles.config(name.as_str())?);
+        summaries.push(ProfileSummary::new(name.as_str(), tracker.report_data(&start, &end)?));
+    }
+    if format == OutputFormat::Json {
+        Ok(create_json_combined_report(start, end, &summaries))
+    } else {
+        Ok(create_human_friendly_combined_report(start, end, date_format, &summaries))
+    }
+}
+
+fn add_line(start: DateTime<Local>, end: DateTime<Local>, break_time: i32, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.add_time(&start, &end, break_time)?;
+    let date_time_format = tracker.date_time_format(time_format);
+    Ok(vec![format!("Added line: from {} to {} with breaks of {} minutes.", start.format(&date_time_format), end.format(&date_time_format), break_time)])
+}
+
+/// The global --time-format option, or the time format of the config.
+fn time_format(matches: &ArgMatches, config: &Config) -> TimeFormat {
+    matches.value_of("time-format")
+        .or_else(|| matches.subcommand().1.and_then(|sub_matches| sub_matches.value_of("time-format")))
+        .and_then(TimeFormat::from_name)
+        .unwrap_or(config.time_format)
+}
+
+/// The global --format option can be given both before and after the subcommand. Without it the config decides.
+fn output_format(matches: &ArgMatches, config: &Config) -> OutputFormat {
+    matches.value_of("format")
+        .or_else(|| matches.subcommand().1.and_then(|sub_matches| sub_matches.value_of("format")))
+        .and_then(OutputFormat::from_name)
+        .unwrap_or(config.output_format)
+}
+
+fn list_lines(start: DateTime<Local>, end: DateTime<Local>, format: OutputFormat, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let rows = tracker.list_time(&start, &end)?;
+    if format == OutputFormat::Json {
+        return Ok(vec![to_json_string(&json!({
+            "start": start.format("%Y-%m-%d").to_string(),
+            "end": end.format("%Y-%m-%d").to_string(),
+            "time_entries": rows.iter().map(time_line_json).collect::<Vec<Value>>()
+        }))]);
+    }
+    let date_format = &tracker.config().date_format;
+    let date_time_format = tracker.date_time_format(time_format);
+    let mut lines = Vec::new();
+    lines.push(format!("Rows from {} to {}:", start.format(date_format), end.format(date_format)));
+    for row in rows {
+        lines.push(format!("id {}: from {} to {} with breaks of {} minutes", row.id, row.start.format(&date_time_format), row.end.format(&date_time_format), row.break_time_minutes));
+    }
+    Ok(lines)
+}
+
+fn edit_line(id: i32, start: Option<(u32, u32)>, end: Option<(u32, u32)>, day: Option<Date<Local>>, break_time: Option<i32>, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let line = tracker.edit_time(id, start, end, day, break_time)?;
+    let date_time_format = tracker.date_time_format(time_format);
+    Ok(vec![format!("Updated line with id {}: from {} to {} with breaks of {} minutes.", id, line.start.format(&date_time_format), line.end.format(&date_time_format), line.break_time_minutes)])
+}
+
+fn delete_line(id: i32, skip_confirmation: bool, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let line = tracker.get_time(id)?;
+    let date_time_format = tracker.date_time_format(time_format);
+    let description = format!("time line with id {}: from {} to {} with breaks of {} minutes", id, line.start.format(&date_time_format), line.end.format(&date_time_format), line.break_time_minutes);
+    if !skip_confirmation && !confirm(format!("Delete {}?", description).as_str())? {
+        return Ok(vec!["Nothing was deleted.".to_string()]);
+    }
+    tracker.delete_time(id)?;
+    Ok(vec![format!("Deleted {}.", description)])
+}
+
+fn report(start: DateTime<Local>, end: DateTime<Local>, csv: bool, format: OutputFormat, time_format: TimeFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    if csv && format == OutputFormat::Json {
+        return Err(NpttError::Validation("-c cannot be combined with --format json.".to_string()));
+    }
+    let data = tracker.report_data(&start, &end)?;
+    if csv {
+        Ok(create_csv_report(data, time_format.strftime()))
+    } else if format == OutputFormat::Json {
+        Ok(create_json_report(data))
+    } else {
+        Ok(create_human_friendly_report(data, tracker.config().date_format.as_str(), time_format.strftime()))
+    }
+}
+
+fn summary_report(start: DateTime<Local>, end: DateTime<Local>, csv: bool, format: OutputFormat, group_by: GroupBy, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    if csv && format == OutputFormat::Json {
+        return Err(NpttError::Validation("-c cannot be combined with --format json.".to_string()));
+    }
+    let data = tracker.report_data(&start, &end)?;
+    if csv {
+        Ok(create_csv_summary(data, group_by))
+    } else if format == OutputFormat::Json {
+        Ok(create_json_summary(data, group_by))
+    } else {
+        Ok(create_human_friendly_summary(data, group_by, tracker.config().date_format.as_str()))
+    }
+}
+
+fn add_flex(flex_time_minutes: i32, date: Date<Local>, comment: Option<&str>, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.add_flex(flex_time_minutes, &date, comment)?;
+    Ok(vec![format!("Inserted flex entry for {} minutes at {} with comment '{}'", flex_time_minutes, date.format(&tracker.config().date_format), comment.unwrap_or(""))])
+}
+
+fn list_flex(start: DateTime<Local>, end: DateTime<Local>, format: OutputFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let rows = tracker.list_flex(&start, &end)?;
+    if format == OutputFormat::Json {
+        return Ok(vec![to_json_string(&json!({
+            "start": start.format("%Y-%m-%d").to_string(),
+            "end": end.format("%Y-%m-%d").to_string(),
+            "flex_entries": rows.iter().map(flex_line_json).collect::<Vec<Value>>()
+        }))]);
+    }
+    let date_format = &tracker.config().date_format;
+    let mut lines = Vec::new();
+    lines.push(format!("Rows from {} to {}:", start.format(date_format), end.format(date_format)));
+    for row in rows {
+        lines.push(format!("id {}: added {} minutes of flex at {} with comment '{}'", row.id, row.flex_minutes, row.date.format(date_format), row.comment));
+    }
+    Ok(lines)
+}
+
+fn delete_flex(id: i32, skip_confirmation: bool, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let line = tracker.get_flex(id)?;
+    let description = format!("flex line with id {}: {} minutes of flex at {} with comment '{}'", id, line.flex_minutes, line.date.format(&tracker.config().date_format), line.comment);
+    if !skip_confirmation && !confirm(format!("Delete {}?", description).as_str())? {
+        return Ok(vec!["Nothing was deleted.".to_string()]);
+    }
+    tracker.delete_flex(id)?;
+    Ok(vec![format!("Deleted {}.", description)])
+}
+
+fn add_absence(category: AbsenceCategory, start: Date<Local>, end: Date<Local>, minutes_per_day: Option<i32>, comment: Option<&str>, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let date_format = &tracker.config().date_format;
+    tracker.add_absence(category, &start, &end, minutes_per_day, comment)?;
+    Ok(vec![format!("Inserted {} absence from {} to {} for {} with comment '{}'", category.name(), start.format(date_format), end.format(date_format),
+                    describe_absence_minutes(minutes_per_day), comment.unwrap_or(""))])
+}
+
+fn list_absence(start: DateTime<Local>, end: DateTime<Local>, format: OutputFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let date_format = &tracker.config().date_format;
+    let rows = tracker.list_absences(&start, &end)?;
+    if format == OutputFormat::Json {
+        return Ok(vec![to_json_string(&json!({
+            "start": start.format("%Y-%m-%d").to_string(),
+            "end": end.format("%Y-%m-%d").to_string(),
+            "absences": rows.iter().map(absence_line_json).collect::<Vec<Value>>()
+        }))]);
+    }
+    let mut lines = Vec::new();
+    lines.push(format!("Rows from {} to {}:", start.format(date_format), end.format(date_format)));
+    for row in rows {
+        lines.push(format!("id {}: {} absence from {} to {} for {} with comment '{}'", row.id, row.category.name(), row.start.format(date_format),
+                           row.end.format(date_format), describe_absence_minutes(row.minutes_per_day), row.comment));
+    }
+    Ok(lines)
+}
+
+fn delete_absence(id: i32, skip_confirmation: bool, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let date_format = &tracker.config().date_format;
+    let line = tracker.get_absence(id)?;
+    let description = format!("{} absence with id {} from {} to {} for {}", line.category.name(), id, line.start.format(date_format),
+                              line.end.format(date_format), describe_absence_minutes(line.minutes_per_day));
+    if !skip_confirmation && !confirm(format!("Delete {}?", description).as_str())? {
+        return Ok(vec!["Nothing was deleted.".to_string()]);
+    }
+    tracker.delete_absence(id)?;
+    Ok(vec![format!("Deleted {}.", description)])
+}
+
+fn describe_absence_minutes(minutes_per_day: Option<i32>) -> String {
+    minutes_per_day.map(|minutes| format!("{} minutes per day", minutes)).unwrap_or_else(|| "whole days".to_string())
+}
+
+fn set_absence_allowance(category: AbsenceCategory, days: f64, year: i32, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.set_absence_allowance(category, year, days)?;
+    Ok(vec![format!("Set {} allowance for {} to {} days.", category.name(), year, days)])
+}
+
+fn export_all(output: Option<&str>, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let dump = tracker.export()?;
+    let json = serde_json::to_string_pretty(&dump).map_err(|err| NpttError::Io("Could not serialize data".to_string(), err.into()))?;
+    match output {
+        Some(path) => {
+            fs::write(path, json).map_err(NpttError::io(format!("Could not write '{}'", path)))?;
+            let mut lines = vec![format!("Exported to {}:", path)];
+            for (name, count) in dump.counts() {
+                lines.push(format!("{}: {}", name, count));
+            }
+            Ok(lines)
+        },
+        None => Ok(vec![json])
+    }
+}
+
+fn import_all(path: &str, mode: ImportMode, skip_confirmation: bool, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let content = fs::read_to_string(path).map_err(NpttError::io(format!("Could not read '{}'", path)))?;
+    let dump: Dump = serde_json::from_str(content.as_str()).map_err(|err| NpttError::Parse(format!("Could not parse '{}': {}", path, err)))?;
+    if mode == ImportMode::Replace && !skip_confirmation && !confirm("Delete all existing data and replace it with the imported data?")? {
+        return Ok(vec!["Nothing was imported.".to_string()]);
+    }
+    let counts = tracker.import(&dump, mode)?;
+    let mut lines = vec![format!("Imported {}:", path)];
+    for count in counts {
+        lines.push(format!("{}: {} added, {} duplicates skipped", count.name, count.added, count.duplicates));
+    }
+    Ok(lines)
+}
+
+fn import_csv(path: &str, dry_run: bool, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let content = fs::read_to_string(path).map_err(NpttError::io(format!("Could not read '{}'", path)))?;
+    let entries = csv_import::parse_csv(content.as_str(), tracker.clock())
+        .map_err(|errors| NpttError::Parse(format!("Could not import '{}', nothing was imported:\n{}", path, errors.join("\n"))))?;
+    let time_entries = entries.iter().filter(|entry| match entry { ImportEntry::Time { .. } => true, ImportEntry::Flex { .. } => false }).count();
+    let summary = format!("{} time entries and {} flex entries", time_entries, entries.len() - time_entries);
+    if dry_run {
+        let mut lines = vec![format!("Would import {} from {}:", summary, path)];
+        lines.extend(entries.iter().map(ImportEntry::describe));
+        return Ok(lines);
+    }
+    tracker.import_entries(&entries)?;
+    Ok(vec![format!("Imported {} from {}.", summary, path)])
+}
+
+fn import_holidays_for_country(country: &str, year: i32, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let (added, total) = tracker.import_holidays_for_country(country, year)?;
+    Ok(vec![format!("Imported {} of {} holidays for {} {}.", added, total, country, year)])
+}
+
+fn import_holidays_from_ics(path: &str, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let content = fs::read_to_string(path).map_err(NpttError::io(format!("Could not read '{}'", path)))?;
+    let holidays = holidays::parse_ics(content.as_str()).map_err(|err| NpttError::Parse(format!("Could not parse '{}': {}", path, err)))?;
+    let added = tracker.add_holidays(&holidays)?;
+    Ok(vec![format!("Imported {} of {} holidays from {}.", added, holidays.len(), path)])
+}
+
+fn add_holiday(date: Date<Local>, name: &str, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.add_holiday(&date, name)?;
+    Ok(vec![format!("Added holiday '{}' at {}.", name, date.format(&tracker.config().date_format))])
+}
+
+fn list_holidays(start: DateTime<Local>, end: DateTime<Local>, format: OutputFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let rows = tracker.list_holidays(&start, &end)?;
+    if format == OutputFormat::Json {
+        return Ok(vec![to_json_string(&json!({
+            "start": start.format("%Y-%m-%d").to_string(),
+            "end": end.format("%Y-%m-%d").to_string(),
+            "holidays": rows.iter().map(holiday_line_json).collect::<Vec<Value>>()
+        }))]);
+    }
+    let date_format = &tracker.config().date_format;
+    let mut lines = Vec::new();
+    lines.push(format!("Holidays from {} to {}:", start.format(date_format), end.format(date_format)));
+    for row in rows {
+        lines.push(format!("{} {}: {}", row.date.format(date_format), row.date.format("%A"), row.name));
+    }
+    Ok(lines)
+}
+
+fn delete_holiday(date: Date<Local>, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.delete_holiday(&date)?;
+    Ok(vec![format!("Deleted holiday at {}.", date.format(&tracker.config().date_format))])
+}
+
+fn add_daily_time_override(start: Date<Local>, minutes_per_weekday: [i32; 7], tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.add_daily_time_override(&start, &minutes_per_weekday)?;
+    Ok(vec![format!("Inserted daily time override from {} with {}.", start.format(&tracker.config().date_format), describe_schedule(&minutes_per_weekday))])
+}
+
+/// Describes a schedule as minutes per weekday, or with a single value if Monday to Friday are the same and weekends have no work.
+fn describe_schedule(minutes_per_weekday: &[i32; 7]) -> String {
+    if minutes_per_weekday[1..5].iter().all(|minutes| *minutes == minutes_per_weekday[0]) && minutes_per_weekday[5] == 0 && minutes_per_weekday[6] == 0 {
+        format!("{} minutes per weekday", minutes_per_weekday[0])
+    } else {
+        WEEKDAY_OPTIONS.iter().zip(minutes_per_weekday.iter())
+            .map(|((name, _), minutes)| format!("{} {}", name, minutes))
+            .collect::<Vec<String>>()
+            .join(", ") + " minutes"
+    }
+}
+
+fn list_daily_time_override(format: OutputFormat, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let date_format = &tracker.config().date_format;
+    let lines = tracker.list_daily_time_overrides()?;
+    if format == OutputFormat::Json {
+        return Ok(vec![to_json_string(&json!({
+            "daily_time_overrides": lines.iter().map(daily_time_override_json).collect::<Vec<Value>>()
+        }))]);
+    }
+    let mut result: Vec<String> = Vec::new();
+    for line in lines {
+        result.push(format!("Daily time override with id {} starting at {} inclusive and ending at {} exclusive with {}.",
+                            line.id,
+                            line.start.format(date_format),
+                            line.end.map(|date| date.format(date_format).to_string()).unwrap_or("never".to_string()),
+                            describe_schedule(&line.minutes_per_weekday)))
+    }
+    Ok(result)
+}
+
+fn stop_daily_time_override(id: i32, end: Date<Local>, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    tracker.stop_daily_time_override(id, &end)?;
+    Ok(vec![format!("Set end date for daily time override with id {} to {}.", id, end.format(&tracker.config().date_format))])
+}
+
+fn delete_daily_time_override(id: i32, skip_confirmation: bool, tracker: &Tracker) -> Result<Vec<String>, NpttError> {
+    let date_format = &tracker.config().date_format;
+    let line = tracker.get_daily_time_override(id)?;
+    let description = format!("daily time override with id {} starting at {} inclusive and ending at {} exclusive with {}",
+                              id,
+                              line.start.format(date_format),
+                              line.end.map(|date| date.format(date_format).to_string()).unwrap_or_else(|| "never".to_string()),
+                              describe_schedule(&line.minutes_per_weekday));
+    if !skip_confirmation && !confirm(format!("Delete {}?", description).as_str())? {
+        return Ok(vec!["Nothing was deleted.".to_string()]);
+    }
+    tracker.delete_daily_time_override(id)?;
+    Ok(vec![format!("Deleted {}.", description)])
+}
+
+fn confirm(question: &str) -> Result<bool, NpttError> {
+    let answer = ask_with_optional_default(format!("{} (y/n)", question).as_str(), Some("n"),
+                                           |value| if ["y", "Y", "n", "N"].contains(&value.as_str()) { Ok(()) } else { Err("Please answer 'y' or 'n'.".to_string()) })?;
+    Ok(answer == "y" || answer == "Y")
+}
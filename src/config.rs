@@ -0,0 +1,175 @@
+use crate::error::NpttError;
+use crate::flex::MidnightAttribution;
+use crate::parsing_utils::{parsers, validators};
+use crate::report_generation::{OutputFormat, TimeFormat};
+use chrono::format::{Item, StrftimeItems};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The weekday names used in `work_days`, starting with Monday.
+pub const WEEKDAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Defaults read from the TOML config file. Flags given on the command line always win over the config.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Default start time of smart-add, HH:mm or 12 hour like 9:30am.
+    pub default_start: Option<String>,
+    /// Default end time of smart-add, HH:mm or 12 hour like 5pm.
+    pub default_end: Option<String>,
+    /// Break in minutes when -b is not given to add-time, stop, status, leave-at and smart-add. The file can also give
+    /// it as a duration like "1h".
+    pub default_break: Option<i32>,
+    /// Expected minutes of work per work day when no daily time override covers the date. Also the default of
+    /// add-daily-time-override. The file can also give it as a duration like "7h30m".
+    pub daily_minutes: i32,
+    /// Which weekdays are work days, starting with Monday.
+    pub work_days: [bool; 7],
+    /// strftime format of the dates in human readable output. JSON and CSV always use `%Y-%m-%d`.
+    pub date_format: String,
+    /// Used when --format is not given.
+    pub output_format: OutputFormat,
+    /// Which day the time of an entry that spans midnight belongs to.
+    pub midnight_attribution: MidnightAttribution,
+    /// 12 or 24 hour times in human readable output and CSV, used when --time-format is not given.
+    pub time_format: TimeFormat
+}
+
+/// The file as written by the user. Every key is optional.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    default_start: Option<String>,
+    default_end: Option<String>,
+    default_break: Option<DurationValue>,
+    daily_minutes: Option<DurationValue>,
+    work_days: Option<Vec<String>>,
+    date_format: Option<String>,
+    output_format: Option<String>,
+    midnight_attribution: Option<String>,
+    time_format: Option<String>
+}
+
+/// A duration in the file, either minutes like `450` or a string like `"7h30m"`, `"7.5h"` or `"1:15"`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DurationValue {
+    Minutes(i32),
+    Text(String)
+}
+
+impl DurationValue {
+    fn minutes(&self, key: &str) -> Result<i32, String> {
+        let minutes = match self {
+            DurationValue::Minutes(minutes) => *minutes,
+            DurationValue::Text(text) => parsers::force_parse_duration(Some(text.as_str())).map_err(|err| format!("{}: {}", key, err))?
+        };
+        if minutes < 0 {
+            return Err(format!("{} cannot be negative.", key));
+        }
+        Ok(minutes)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            default_start: None,
+            default_end: None,
+            default_break: None,
+            daily_minutes: 8 * 60,
+            work_days: [true, true, true, true, true, false, false],
+            date_format: "%Y-%m-%d".to_string(),
+            output_format: OutputFormat::Human,
+            midnight_attribution: MidnightAttribution::Start,
+            time_format: TimeFormat::H24
+        }
+    }
+}
+
+impl Config {
+    /// `nptt/config.toml` in the XDG config dir, e.g. `~/.config/nptt/config.toml`.
+    pub fn default_location() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("nptt").join("config.toml"))
+    }
+
+    /// The dir with the config files of the profiles other than the default profile, `nptt/profiles` in the XDG config dir.
+    pub fn profiles_location() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("nptt").join("profiles"))
+    }
+
+    pub fn read(path: &Path) -> Result<Config, NpttError> {
+        let content = fs::read_to_string(path).map_err(NpttError::io(format!("Could not read config file '{}'", path.display())))?;
+        Config::parse(content.as_str()).map_err(|err| NpttError::Parse(format!("Invalid config file '{}': {}", path.display(), err)))
+    }
+
+    /// Parses and validates the content of a config file. Missing keys get the default values.
+    pub fn parse(content: &str) -> Result<Config, String> {
+        let file: ConfigFile = toml::from_str(content).map_err(|err| err.to_string())?;
+        let default = Config::default();
+        for time in file.default_start.iter().chain(file.default_end.iter()) {
+            validators::time_validator(time.clone())?;
+        }
+        let default_break = file.default_break.map(|value| value.minutes("default_break")).transpose()?;
+        let daily_minutes = file.daily_minutes.map(|value| value.minutes("daily_minutes")).transpose()?;
+        let work_days = match file.work_days {
+            None => default.work_days,
+            Some(names) => {
+                let mut work_days = [false; 7];
+                for name in names {
+                    let index = WEEKDAY_NAMES.iter().position(|weekday| *weekday == name.as_str())
+                        .ok_or_else(|| format!("'{}' in work_days is not one of {}.", name, WEEKDAY_NAMES.join(", ")))?;
+                    work_days[index] = true;
+                }
+                work_days
+            }
+        };
+        if let Some(date_format) = &file.date_format {
+            if StrftimeItems::new(date_format).any(|item| matches!(item, Item::Error)) {
+                return Err(format!("'{}' is not a valid date_format.", date_format));
+            }
+        }
+        let output_format = match file.output_format {
+            None => default.output_format,
+            Some(name) => OutputFormat::from_name(name.as_str())
+                .ok_or_else(|| format!("'{}' is not a valid output_format. Use one of {}.", name, OutputFormat::NAMES.join(", ")))?
+        };
+        let midnight_attribution = match file.midnight_attribution {
+            None => default.midnight_attribution,
+            Some(name) => MidnightAttribution::from_name(name.as_str())
+                .ok_or_else(|| format!("'{}' is not a valid midnight_attribution. Use one of {}.", name, MidnightAttribution::NAMES.join(", ")))?
+        };
+        let time_format = match file.time_format {
+            None => default.time_format,
+            Some(name) => TimeFormat::from_name(name.as_str())
+                .ok_or_else(|| format!("'{}' is not a valid time_format. Use one of {}.", name, TimeFormat::NAMES.join(", ")))?
+        };
+        Ok(Config {
+            default_start: file.default_start,
+            default_end: file.default_end,
+            default_break,
+            daily_minutes: daily_minutes.unwrap_or(default.daily_minutes),
+            work_days,
+            date_format: file.date_format.unwrap_or(default.date_format),
+            output_format,
+            midnight_attribution,
+            time_format
+        })
+    }
+
+    /// `daily_minutes` on work days and 0 on the other days, starting with Monday.
+    pub fn minutes_per_weekday(&self, daily_minutes: i32) -> [i32; 7] {
+        let mut minutes_per_weekday = [0; 7];
+        for (minutes, work_day) in minutes_per_weekday.iter_mut().zip(self.work_days.iter()) {
+            if *work_day {
+                *minutes = daily_minutes;
+            }
+        }
+        minutes_per_weekday
+    }
+
+    /// The expected minutes of work per weekday when no daily time override covers the date.
+    pub fn default_schedule(&self) -> [i32; 7] {
+        self.minutes_per_weekday(self.daily_minutes)
+    }
+}
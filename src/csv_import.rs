@@ -0,0 +1,155 @@
+use crate::parsing_utils::{validators, parsers};
+use crate::clock::Clock;
+use crate::tracker::check_time_line;
+use chrono::{DateTime, Local, Date, Duration, NaiveTime};
+
+/// Header of the layout that `report -c` produces. Extra columns after these are allowed.
+pub const REPORT_HEADER: &str = "Date,Start,End,Break,Flex (minutes)";
+/// Header of the simple layout with one time or flex entry per row.
+pub const SIMPLE_HEADER: &str = "Type,Date,Start,End,Break,Minutes,Comment";
+
+/// Column in the report layout where the flex table and the absence table start.
+const REPORT_TABLE_COLUMN: usize = 7;
+
+pub enum ImportEntry {
+    Time { start: DateTime<Local>, end: DateTime<Local>, break_time_minutes: i32 },
+    Flex { date: Date<Local>, minutes: i32, comment: Option<String> }
+}
+
+impl ImportEntry {
+    pub fn describe(&self) -> String {
+        match self {
+            ImportEntry::Time { start, end, break_time_minutes } =>
+                format!("time from {} to {} with breaks of {} minutes", start.format("%Y-%m-%d %H:%M"), end.format("%H:%M"), break_time_minutes),
+            ImportEntry::Flex { date, minutes, comment } =>
+                format!("{} minutes of flex at {} with comment '{}'", minutes, date.format("%Y-%m-%d"), comment.as_deref().unwrap_or(""))
+        }
+    }
+}
+
+enum ReportTable {
+    None,
+    Flex,
+    Other
+}
+
+/// Reads time and flex entries from either the `report -c` layout or the simple layout, recognized by the header.
+/// All rows are validated and every error is returned, prefixed with its line number.
+pub fn parse_csv(content: &str, clock: &Clock) -> Result<Vec<ImportEntry>, Vec<String>> {
+    let mut lines = content.lines().map(|line| line.trim_end_matches('\r')).enumerate().map(|(index, line)| (index + 1, line));
+    let header = match lines.next() {
+        Some((_, header)) => header,
+        None => return Err(vec!["The file is empty.".to_string()])
+    };
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    if header.starts_with(REPORT_HEADER) {
+        let mut date: Option<&str> = None;
+        let mut table = ReportTable::None;
+        for (line_number, line) in lines {
+            let columns: Vec<&str> = line.split(',').collect();
+            if !columns[0].is_empty() {
+                date = Some(columns[0]);
+            }
+            if columns.len() > 2 && !columns[1].is_empty() {
+                match date {
+                    Some(date) => push_result(parse_time_entry(date, columns[1], columns[2], columns.get(3).cloned().unwrap_or(""), clock), line_number, &mut entries, &mut errors),
+                    None => errors.push(format!("Line {}: time entry without a date.", line_number))
+                }
+            }
+            if columns.len() > REPORT_TABLE_COLUMN && !columns[REPORT_TABLE_COLUMN].is_empty() {
+                let table_column = columns[REPORT_TABLE_COLUMN];
+                if table_column == "Date for flex" {
+                    table = ReportTable::Flex;
+                } else if table_column == "Absence category" {
+                    table = ReportTable::Other;
+                } else if let ReportTable::Flex = table {
+                    let comment = columns[REPORT_TABLE_COLUMN + 2..].join(",");
+                    push_result(parse_flex_entry(strip_utc_offset(table_column), columns.get(REPORT_TABLE_COLUMN + 1).cloned().unwrap_or(""), comment.as_str(), clock), line_number, &mut entries, &mut errors);
+                }
+            }
+        }
+    } else if header == SIMPLE_HEADER {
+        for (line_number, line) in lines.filter(|(_, line)| !line.is_empty()) {
+            let columns: Vec<&str> = line.splitn(7, ',').collect();
+            if columns.len() < 6 {
+                errors.push(format!("Line {}: expected the columns {}.", line_number, SIMPLE_HEADER));
+                continue;
+            }
+            let result = match columns[0] {
+                "time" => parse_time_entry(columns[1], columns[2], columns[3], columns[4], clock),
+                "flex" => parse_flex_entry(columns[1], columns[5], columns.get(6).cloned().unwrap_or(""), clock),
+                other => Err(format!("Unknown type '{}', expected 'time' or 'flex'.", other))
+            };
+            push_result(result, line_number, &mut entries, &mut errors);
+        }
+    } else {
+        errors.push(format!("Line 1: unknown header. Use the layout from 'report -c' or the header '{}'.", SIMPLE_HEADER));
+    }
+    if errors.is_empty() {
+        Ok(join_split_at_midnight(entries))
+    } else {
+        Err(errors)
+    }
+}
+
+fn push_result(result: Result<ImportEntry, String>, line_number: usize, entries: &mut Vec<ImportEntry>, errors: &mut Vec<String>) {
+    match result {
+        Ok(entry) => entries.push(entry),
+        Err(error) => errors.push(format!("Line {}: {}", line_number, error))
+    }
+}
+
+/// With `midnight_attribution = "split"`, `report -c` writes an entry spanning midnight as one row per day. So a time
+/// entry that starts at midnight, where the previous time entry ended, is joined with it, as long as the result is
+/// not longer than 24 hours. The breaks are added up.
+fn join_split_at_midnight(entries: Vec<ImportEntry>) -> Vec<ImportEntry> {
+    let mut joined: Vec<ImportEntry> = Vec::new();
+    let mut last_time_entry: Option<usize> = None;
+    for entry in entries {
+        if let ImportEntry::Time { start, end, break_time_minutes } = entry {
+            if let Some(ImportEntry::Time { start: previous_start, end: previous_end, break_time_minutes: previous_break }) = last_time_entry.map(|index| &mut joined[index]) {
+                if *previous_end == start && start.time() == NaiveTime::from_hms(0, 0, 0) && end - *previous_start <= Duration::days(1) {
+                    *previous_end = end;
+                    *previous_break += break_time_minutes;
+                    continue;
+                }
+            }
+            last_time_entry = Some(joined.len());
+        }
+        joined.push(entry);
+    }
+    joined
+}
+
+/// `report -c` writes the dates of flex entries with the UTC offset, e.g. `2019-11-12+01:00`.
+fn strip_utc_offset(date: &str) -> &str {
+    match date.char_indices().nth(10) {
+        Some((index, '+')) | Some((index, '-')) => &date[..index],
+        _ => date
+    }
+}
+
+fn parse_time_entry(date: &str, start: &str, end: &str, break_time: &str, clock: &Clock) -> Result<ImportEntry, String> {
+    validators::day_validator(date.to_string())?;
+    validators::time_validator(start.to_string())?;
+    validators::time_validator(end.to_string())?;
+    let break_time = if break_time.is_empty() { "0" } else { break_time };
+    validators::unsigned_number_validator(break_time.to_string())?;
+    let to_datetime = |time: &str| parsers::force_parse_datetime(Some(time), Some(date), clock).map_err(|err| err.to_string());
+    let (start, end) = (to_datetime(start)?, to_datetime(end)?);
+    // An end before the start is on the next day, as for add-time, e.g. a night shift from 22:00 to 02:00.
+    let end = if end < start { end + Duration::days(1) } else { end };
+    check_time_line(&start, &end).map_err(|err| err.to_string())?;
+    Ok(ImportEntry::Time { start, end, break_time_minutes: parsers::force_parse_integer(Some(break_time)).map_err(|err| err.to_string())? })
+}
+
+fn parse_flex_entry(date: &str, minutes: &str, comment: &str, clock: &Clock) -> Result<ImportEntry, String> {
+    validators::day_validator(date.to_string())?;
+    validators::signed_minute_validator(minutes.to_string())?;
+    Ok(ImportEntry::Flex {
+        date: parsers::get_date_from_string(date, clock).map_err(|err| err.to_string())?,
+        minutes: parsers::force_parse_integer(Some(minutes)).map_err(|err| err.to_string())?,
+        comment: if comment.is_empty() { None } else { Some(comment.to_string()) }
+    })
+}
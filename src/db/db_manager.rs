@@ -2,44 +2,225 @@ extern crate rusqlite;
 extern crate chrono;
 
 use rusqlite::{Connection, Error, NO_PARAMS, Rows, params, Error::QueryReturnedNoRows};
-use rusqlite::types::{Null};
-use chrono::{DateTime, Local, TimeZone, Date, NaiveDate};
+use rusqlite::types::{Null, Type};
+use chrono::{DateTime, Local, TimeZone, Date, NaiveDate, Weekday};
 use std::result::*;
 use std::option::Option::Some;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+use crate::clock::Clock;
+use crate::db::storage::Storage;
 
-const DB_VERSION: i8 = 1;
+/// A migration upgrades the schema by exactly one version.
+pub type Migration = fn(&Connection) -> Result<(), Error>;
+
+/// All migrations in order. The first one upgrades a version 1 DB to version 2, the second one
+/// from version 2 to version 3 and so on. Never change or remove a migration that has been released,
+/// add a new one to the end instead.
+pub const MIGRATIONS: &[Migration] = &[create_absence_tables, create_holiday_table, create_running_timer_table, create_weekday_schedules];
+
+fn create_absence_tables(connection: &Connection) -> Result<(), Error> {
+    connection.execute_batch("CREATE TABLE absence (\
+            id INTEGER PRIMARY KEY,\
+            category TEXT NOT NULL,\
+            startDate TEXT NOT NULL,\
+            endDate TEXT NOT NULL,\
+            minutesPerDay INTEGER,\
+            comment TEXT\
+        );\
+        CREATE TABLE absenceAllowance (\
+            category TEXT NOT NULL,\
+            year INTEGER NOT NULL,\
+            days REAL NOT NULL,\
+            PRIMARY KEY (category, year)\
+        );")
+}
+
+fn create_holiday_table(connection: &Connection) -> Result<(), Error> {
+    connection.execute("CREATE TABLE holiday (\
+            date TEXT PRIMARY KEY,\
+            name TEXT NOT NULL\
+        )", NO_PARAMS).map(|_| ())
+}
+
+fn create_running_timer_table(connection: &Connection) -> Result<(), Error> {
+    connection.execute("CREATE TABLE runningTimer (\
+            start INTEGER NOT NULL\
+        )", NO_PARAMS).map(|_| ())
+}
+
+/// Replaces the single minutesOfWork of the daily time overrides with one value per weekday.
+/// Existing overrides keep their minutes on Monday to Friday and get no work on weekends.
+fn create_weekday_schedules(connection: &Connection) -> Result<(), Error> {
+    connection.execute_batch("CREATE TABLE dailyTimeSchedule (\
+            id INTEGER PRIMARY KEY,\
+            startDate TEXT NOT NULL,\
+            endDate TEXT,\
+            mondayMinutes INTEGER NOT NULL,\
+            tuesdayMinutes INTEGER NOT NULL,\
+            wednesdayMinutes INTEGER NOT NULL,\
+            thursdayMinutes INTEGER NOT NULL,\
+            fridayMinutes INTEGER NOT NULL,\
+            saturdayMinutes INTEGER NOT NULL,\
+            sundayMinutes INTEGER NOT NULL\
+        );\
+        INSERT INTO dailyTimeSchedule(id, startDate, endDate, mondayMinutes, tuesdayMinutes, wednesdayMinutes, thursdayMinutes, fridayMinutes, saturdayMinutes, sundayMinutes) \
+            SELECT id, startDate, endDate, minutesOfWork, minutesOfWork, minutesOfWork, minutesOfWork, minutesOfWork, 0, 0 FROM dailyTime;\
+        DROP TABLE dailyTime;\
+        ALTER TABLE dailyTimeSchedule RENAME TO dailyTime;")
+}
+
+const DAILY_TIME_COLUMNS: &str = "id, startDate, endDate, mondayMinutes, tuesdayMinutes, wednesdayMinutes, thursdayMinutes, fridayMinutes, saturdayMinutes, sundayMinutes";
 
 pub struct DbConnection {
-    connection: Connection
+    connection: Connection,
+    path: PathBuf,
+    clock: Clock
+}
+
+#[derive(Debug)]
+pub enum DbInitError {
+    Db(Error),
+    Backup(io::Error),
+    UnknownVersion(i8)
+}
+
+impl fmt::Display for DbInitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DbInitError::Db(err) => write!(f, "{}", err),
+            DbInitError::Backup(err) => write!(f, "Could not back up the DB before migrating it: {}", err),
+            DbInitError::UnknownVersion(version) => write!(f, "The DB has version {} which is newer than this program supports. Please upgrade.", version)
+        }
+    }
 }
 
+impl From<Error> for DbInitError {
+    fn from(err: Error) -> Self {
+        DbInitError::Db(err)
+    }
+}
+
+#[derive(Clone)]
 pub struct DateLine {
+    pub id: i32,
     pub start: DateTime<Local>,
     pub end: DateTime<Local>,
     pub break_time_minutes: i32,
     pub date: NaiveDate
 }
 
+#[derive(Clone)]
 pub struct FlexLine {
+    pub id: i32,
     pub date: DateTime<Local>,
     pub flex_minutes: i32,
     pub comment: String
 }
 
+/// A work schedule from `start` (inclusive) to `end` (exclusive).
+#[derive(Clone)]
 pub struct DailyTimeOverrideLine {
     pub id: i32,
     pub start: NaiveDate,
     pub end: Option<NaiveDate>,
-    pub minutes_of_work: i32
+    /// Minutes of work per weekday, starting with Monday.
+    pub minutes_per_weekday: [i32; 7]
+}
+
+impl DailyTimeOverrideLine {
+    pub fn minutes_for(&self, weekday: Weekday) -> i32 {
+        self.minutes_per_weekday[weekday.num_days_from_monday() as usize]
+    }
+
+    pub fn covers(&self, date: &NaiveDate) -> bool {
+        *date >= self.start && self.end.map(|end| *date < end).unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum AbsenceCategory {
+    Vacation,
+    Sick,
+    Parental,
+    Other
+}
+
+impl AbsenceCategory {
+    pub const NAMES: [&'static str; 4] = ["vacation", "sick", "parental", "other"];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AbsenceCategory::Vacation => "vacation",
+            AbsenceCategory::Sick => "sick",
+            AbsenceCategory::Parental => "parental",
+            AbsenceCategory::Other => "other"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<AbsenceCategory> {
+        match name {
+            "vacation" => Some(AbsenceCategory::Vacation),
+            "sick" => Some(AbsenceCategory::Sick),
+            "parental" => Some(AbsenceCategory::Parental),
+            "other" => Some(AbsenceCategory::Other),
+            _ => None
+        }
+    }
+}
+
+/// Absence from `start` to `end`, both inclusive. Without `minutes_per_day` the absence covers the whole day.
+#[derive(Clone)]
+pub struct AbsenceLine {
+    pub id: i32,
+    pub category: AbsenceCategory,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub minutes_per_day: Option<i32>,
+    pub comment: String
+}
+
+impl AbsenceLine {
+    pub fn covers(&self, date: &NaiveDate) -> bool {
+        *date >= self.start && *date <= self.end
+    }
+}
+
+#[derive(Clone)]
+pub struct HolidayLine {
+    pub date: NaiveDate,
+    pub name: String
+}
+
+#[derive(Clone)]
+pub struct AbsenceAllowanceLine {
+    pub category: AbsenceCategory,
+    pub year: i32,
+    pub days: f64
 }
 
-pub fn create_connection<P: AsRef<Path>>(path: P) -> Result<DbConnection, Error> {
-    return Ok(DbConnection{ connection: Connection::open(path)?});
+/// Parses a date stored as `YYYY-MM-DD` in the column with the index.
+fn parse_db_date(index: usize, date: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|err| Error::FromSqlConversionFailure(index, Type::Text, Box::new(err)))
+}
+
+fn parse_db_category(index: usize, category: &str) -> Result<AbsenceCategory, Error> {
+    AbsenceCategory::from_name(category)
+        .ok_or_else(|| Error::FromSqlConversionFailure(index, Type::Text, format!("Unknown absence category '{}'.", category).into()))
+}
+
+pub fn create_connection<P: AsRef<Path>>(path: P, clock: Clock) -> Result<DbConnection, Error> {
+    Ok(DbConnection{ connection: Connection::open(&path)?, path: path.as_ref().to_path_buf(), clock })
 }
 
 impl DbConnection {
-    pub fn create_tables(&self) -> Result<(), Error> {
+    pub fn create_tables(&self) -> Result<(), DbInitError> {
+        self.create_tables_with_migrations(MIGRATIONS)
+    }
+
+    /// Creates the version 1 schema if the DB is empty and then migrates the DB to the latest version.
+    /// Schema changes must be added as a migration, not to the statements below.
+    pub fn create_tables_with_migrations(&self, migrations: &[Migration]) -> Result<(), DbInitError> {
         self.connection.execute("CREATE TABLE IF NOT EXISTS time (\
             id INTEGER PRIMARY KEY,\
             start INTEGER NOT NULL,\
@@ -61,68 +242,259 @@ impl DbConnection {
             endDate TEXT,\
             minutesOfWork INTEGER NOT NULL\
         )", NO_PARAMS)?;
-        self.init_version()?;
-        return Ok(());
+        self.init_version(migrations)
     }
 
-    fn init_version(&self) -> Result<(), Error> {
-        match self.connection.query_row("SELECT version FROM version", NO_PARAMS, |row| row.get::<usize, i8>(0)) {
-            Err(QueryReturnedNoRows) => self.connection.execute("INSERT INTO version(version) VALUES(?)", params![DB_VERSION]).map(|_| ()),
-            Ok(version) => if version != DB_VERSION {
-                unimplemented!("No migration implemented yet")
+    fn init_version(&self, migrations: &[Migration]) -> Result<(), DbInitError> {
+        let latest_version = migrations.len() as i8 + 1;
+        match self.get_version() {
+            Err(QueryReturnedNoRows) => {
+                self.connection.execute("INSERT INTO version(version) VALUES(1)", NO_PARAMS)?;
+                self.migrate(1, migrations)
+            },
+            Ok(version) => if version > latest_version {
+                Err(DbInitError::UnknownVersion(version))
+            } else if version < latest_version {
+                self.backup(version)?;
+                self.migrate(version, migrations)
             } else {
                 Ok(())
             },
-            err => err.map(|_| ())
+            Err(err) => Err(err.into())
         }
     }
 
-    pub fn insert_time(&self, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), Error> {
-        let mut statement = self.connection.prepare("INSERT INTO time(date, start, end, breakTimeMinutes) \
-                                                  VALUES(?,?,?,?)")?;
-        statement.execute(params![start.format("%Y-%m-%d").to_string(), start.timestamp(), end.timestamp(), break_time_minutes as i64])?;
-        return Ok(());
-    }
-
-    pub fn get_num_time_entries(&self) -> Result<i32, Error> {
-        self.connection.query_row("SELECT COUNT(*) FROM time WHERE start < ?", params![Local::now().timestamp()], |row| row.get(0))
+    fn migrate(&self, from_version: i8, migrations: &[Migration]) -> Result<(), DbInitError> {
+        for (index, migration) in migrations.iter().enumerate().skip(from_version as usize - 1) {
+            let new_version = index as i8 + 2;
+            self.in_transaction(|db| {
+                migration(&db.connection)?;
+                db.connection.execute("UPDATE version SET version=?", params![new_version])?;
+                Ok(())
+            })?;
+        }
+        Ok(())
     }
 
-    pub fn get_date_for_last_entry(&self) -> Result<NaiveDate, Error> {
-        self.connection.query_row("SELECT date FROM time WHERE start < ? ORDER BY start DESC LIMIT 1", params![Local::now().timestamp()],
-                                  |row| Ok(NaiveDate::parse_from_str(row.get::<usize, String>(0)?.as_str(), "%Y-%m-%d").unwrap()))
+    /// Copies the DB file to `<db file>.v<version>.bak` so that a failed or unwanted migration can be undone by hand.
+    fn backup(&self, version: i8) -> Result<(), DbInitError> {
+        let mut backup_path = self.path.clone().into_os_string();
+        backup_path.push(format!(".v{}.bak", version));
+        let backup_path = PathBuf::from(backup_path);
+        fs::copy(&self.path, &backup_path).map_err(DbInitError::Backup)?;
+        Ok(())
     }
 
-    pub fn list_times(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<DateLine>, Error> {
-        let mut statement = self.connection.prepare("SELECT start, end, breakTimeMinutes, date FROM time WHERE start > ? AND end < ? ORDER BY start")?;
-        let rows = statement.query(&[from.timestamp(), to.timestamp()])?;
-        return DbConnection::extract_time_rows(rows);
+    /// Runs `action` in a transaction which is committed if the action succeeds and rolled back otherwise.
+    /// Transactions can be nested, a nested transaction is only committed together with the outer one.
+    pub fn in_transaction<T, F>(&self, action: F) -> Result<T, Error>
+        where
+            F: FnOnce(&DbConnection) -> Result<T, Error>
+    {
+        self.connection.execute_batch("SAVEPOINT nptt")?;
+        match action(self) {
+            Ok(value) => {
+                self.connection.execute_batch("RELEASE nptt")?;
+                Ok(value)
+            },
+            Err(err) => {
+                self.connection.execute_batch("ROLLBACK TO nptt; RELEASE nptt")?;
+                Err(err)
+            }
+        }
     }
 
     fn extract_time_rows(mut rows: Rows) -> Result<Vec<DateLine>, Error> {
         let mut date_lines: Vec<DateLine> = Vec::new();
         while let Some(row) = rows.next()? {
-            let date: String = row.get(3)?;
+            let date: String = row.get(4)?;
             date_lines.push(DateLine {
-                start: Local.timestamp(row.get(0)?, 0),
-                end: Local.timestamp(row.get(1)?, 0),
-                break_time_minutes: row.get(2)?,
-                date: NaiveDate::parse_from_str(date.as_str(), "%Y-%m-%d").expect("Could not parse date from DB.")
+                id: row.get(0)?,
+                start: Local.timestamp(row.get(1)?, 0),
+                end: Local.timestamp(row.get(2)?, 0),
+                break_time_minutes: row.get(3)?,
+                date: parse_db_date(4, &date)?
             });
         }
         return Ok(date_lines);
     }
 
-    pub fn calculate_flex_hours(&self) -> Result<f64, Error> {
-        let flex_seconds_from_time: i32 = self.connection.query_row("SELECT IFNULL(SUM(flexTime.seconds_per_day - (CASE WHEN strftime('%w',flexTime.date) IN ('0','6') THEN 0 ELSE IFNULL(dailyTime.minutesOfWork,8*60) END)*60),0) FROM \
-            (SELECT SUM(end - start - (breakTimeMinutes*60)) as seconds_per_day, date FROM time GROUP BY date) flexTime LEFT JOIN \
-            (SELECT minutesOfWork, startDate, endDate FROM dailyTime) dailyTime ON flexTime.date >= dailyTime.startDate AND (dailyTime.endDate IS NULL OR flexTime.date < dailyTime.endDate)",
-               NO_PARAMS, |row| row.get(0))?;
-        let flex_minutes_from_flex: i32 = self.connection.query_row("SELECT IFNULL(SUM(flexMinutes),0) FROM flex", NO_PARAMS, |row| row.get(0))?;
-        Ok(flex_seconds_from_time as f64 / 60.0 / 60.0 + flex_minutes_from_flex as f64 / 60.0)
+    fn extract_flex_rows(mut rows: Rows) -> Result<Vec<FlexLine>, Error> {
+        let mut flex_lines: Vec<FlexLine> = Vec::new();
+        while let Some(row) = rows.next()? {
+            flex_lines.push(FlexLine {
+                id: row.get(0)?,
+                flex_minutes: row.get(1)?,
+                date: Local.timestamp(row.get(2)?, 0),
+                comment: row.get(3).unwrap_or_default()
+            });
+        }
+        Ok(flex_lines)
+    }
+
+    fn extract_daily_time_override_rows(mut rows: Rows) -> Result<Vec<DailyTimeOverrideLine>, Error> {
+        let mut daily_times: Vec<DailyTimeOverrideLine> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let start_date: String = row.get(1)?;
+            let end_date: Option<String> = row.get(2)?;
+            let mut minutes_per_weekday = [0; 7];
+            for (index, minutes) in minutes_per_weekday.iter_mut().enumerate() {
+                *minutes = row.get(index + 3)?;
+            }
+            daily_times.push(DailyTimeOverrideLine {
+                id: row.get(0)?,
+                start: parse_db_date(1, &start_date)?,
+                end: end_date.map(|date| parse_db_date(2, &date)).transpose()?,
+                minutes_per_weekday
+            })
+        }
+        Ok(daily_times)
+    }
+
+    fn delete_by_id(connection: &Connection, sql: &str, id: i32) -> Result<(), Error> {
+        let result = connection.execute(sql, params![id])?;
+        if result == 0 {
+            Err(Error::QueryReturnedNoRows)
+        } else {
+            Ok(())
+        }
     }
 
-    pub fn add_flex(&self, flex_minutes: i32, date: &Date<Local>, comment: Option<&str>) -> Result<(), Error> {
+    fn extract_absence_rows(mut rows: Rows) -> Result<Vec<AbsenceLine>, Error> {
+        let mut absences: Vec<AbsenceLine> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let category: String = row.get(1)?;
+            let start_date: String = row.get(2)?;
+            let end_date: String = row.get(3)?;
+            absences.push(AbsenceLine {
+                id: row.get(0)?,
+                category: parse_db_category(1, &category)?,
+                start: parse_db_date(2, &start_date)?,
+                end: parse_db_date(3, &end_date)?,
+                minutes_per_day: row.get(4)?,
+                comment: row.get::<usize, Option<String>>(5)?.unwrap_or_default()
+            });
+        }
+        Ok(absences)
+    }
+
+    fn extract_absence_allowance_rows(mut rows: Rows) -> Result<Vec<AbsenceAllowanceLine>, Error> {
+        let mut allowances: Vec<AbsenceAllowanceLine> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let category: String = row.get(0)?;
+            allowances.push(AbsenceAllowanceLine {
+                category: parse_db_category(0, &category)?,
+                year: row.get(1)?,
+                days: row.get(2)?
+            });
+        }
+        Ok(allowances)
+    }
+
+    fn extract_holiday_rows(mut rows: Rows) -> Result<Vec<HolidayLine>, Error> {
+        let mut holidays: Vec<HolidayLine> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let date: String = row.get(0)?;
+            holidays.push(HolidayLine {
+                date: parse_db_date(0, &date)?,
+                name: row.get(1)?
+            });
+        }
+        Ok(holidays)
+    }
+}
+
+impl Storage for DbConnection {
+    fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    fn get_version(&self) -> Result<i8, Error> {
+        self.connection.query_row("SELECT version FROM version", NO_PARAMS, |row| row.get(0))
+    }
+
+    fn transaction(&self, action: &mut dyn FnMut(&dyn Storage) -> Result<(), Error>) -> Result<(), Error> {
+        self.in_transaction(|db| action(db))
+    }
+
+    fn insert_time(&self, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), Error> {
+        let mut statement = self.connection.prepare("INSERT INTO time(date, start, end, breakTimeMinutes) \
+                                                  VALUES(?,?,?,?)")?;
+        statement.execute(params![start.format("%Y-%m-%d").to_string(), start.timestamp(), end.timestamp(), break_time_minutes as i64])?;
+        return Ok(());
+    }
+
+    fn update_time(&self, id: i32, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), Error> {
+        let mut statement = self.connection.prepare("UPDATE time SET date=?, start=?, end=?, breakTimeMinutes=? WHERE id=?")?;
+        let result = statement.execute(params![start.format("%Y-%m-%d").to_string(), start.timestamp(), end.timestamp(), break_time_minutes as i64, id])?;
+        if result == 0 {
+            Err(Error::QueryReturnedNoRows)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_time(&self, id: i32) -> Result<DateLine, Error> {
+        let mut statement = self.connection.prepare("SELECT id, start, end, breakTimeMinutes, date FROM time WHERE id = ?")?;
+        let rows = statement.query(params![id])?;
+        DbConnection::extract_time_rows(rows)?.pop().ok_or(Error::QueryReturnedNoRows)
+    }
+
+    fn delete_time(&self, id: i32) -> Result<(), Error> {
+        DbConnection::delete_by_id(&self.connection, "DELETE FROM time WHERE id=?", id)
+    }
+
+    fn get_num_time_entries(&self) -> Result<i32, Error> {
+        self.connection.query_row("SELECT (SELECT COUNT(*) FROM time WHERE start < ?1) + (SELECT COUNT(*) FROM runningTimer WHERE start < ?1)",
+                                  params![self.clock.now().timestamp()], |row| row.get(0))
+    }
+
+    fn get_date_for_last_entry(&self) -> Result<NaiveDate, Error> {
+        let last_entry = match self.connection.query_row("SELECT date FROM time WHERE start < ? ORDER BY start DESC LIMIT 1", params![self.clock.now().timestamp()],
+                                  |row| parse_db_date(0, &row.get::<usize, String>(0)?)) {
+            Ok(date) => Some(date),
+            Err(QueryReturnedNoRows) => None,
+            Err(err) => return Err(err)
+        };
+        let running_timer = self.get_running_timer()?.map(|start| start.naive_local().date());
+        last_entry.max(running_timer).ok_or(QueryReturnedNoRows)
+    }
+
+    fn start_timer(&self, start: &DateTime<Local>) -> Result<(), Error> {
+        self.connection.execute("INSERT INTO runningTimer(start) VALUES(?)", params![start.timestamp()])?;
+        Ok(())
+    }
+
+    fn get_running_timer(&self) -> Result<Option<DateTime<Local>>, Error> {
+        match self.connection.query_row("SELECT start FROM runningTimer", NO_PARAMS, |row| row.get(0)) {
+            Ok(start) => Ok(Some(Local.timestamp(start, 0))),
+            Err(QueryReturnedNoRows) => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+
+    fn stop_timer(&self, end: &DateTime<Local>, break_time_minutes: i32) -> Result<DateTime<Local>, Error> {
+        self.in_transaction(|db| {
+            let start = db.get_running_timer()?.ok_or(QueryReturnedNoRows)?;
+            db.insert_time(&start, end, break_time_minutes)?;
+            db.connection.execute("DELETE FROM runningTimer", NO_PARAMS)?;
+            Ok(start)
+        })
+    }
+
+    fn list_times(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<DateLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT id, start, end, breakTimeMinutes, date FROM time WHERE start > ? AND end < ? ORDER BY start")?;
+        let rows = statement.query(&[from.timestamp(), to.timestamp()])?;
+        return DbConnection::extract_time_rows(rows);
+    }
+
+    fn list_all_times(&self) -> Result<Vec<DateLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT id, start, end, breakTimeMinutes, date FROM time ORDER BY start")?;
+        let rows = statement.query(NO_PARAMS)?;
+        DbConnection::extract_time_rows(rows)
+    }
+
+    fn add_flex(&self, flex_minutes: i32, date: &Date<Local>, comment: Option<&str>) -> Result<(), Error> {
         let mut statement = self.connection.prepare("INSERT INTO flex(flexMinutes, date, comment) VALUES(?,?,?)")?;
         match comment {
             Some(comment) => statement.execute(params![flex_minutes.to_string().as_str(), date.and_hms(0,0,0).timestamp().to_string().as_str(), comment]).map(|_| ())?,
@@ -131,27 +503,37 @@ impl DbConnection {
         Ok(())
     }
 
-    pub fn list_flex(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<FlexLine>, Error> {
-        let mut statement = self.connection.prepare("SELECT flexMinutes, date, comment FROM flex WHERE date >= ? AND date < ? ORDER BY date")?;
-        let mut rows = statement.query(&[from.timestamp(), to.timestamp()])?;
-        let mut flex_lines: Vec<FlexLine> = Vec::new();
-        while let Some(row) = rows.next()? {
-            flex_lines.push(FlexLine {
-                flex_minutes: row.get(0)?,
-                date: Local.timestamp(row.get(1)?, 0),
-                comment: row.get(2).unwrap_or("".to_string())
-            });
-        }
-        return Ok(flex_lines);
+    fn list_flex(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<FlexLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT id, flexMinutes, date, comment FROM flex WHERE date >= ? AND date < ? ORDER BY date")?;
+        let rows = statement.query([from.timestamp(), to.timestamp()])?;
+        DbConnection::extract_flex_rows(rows)
     }
 
-    pub fn add_daily_time_override(&self, start: &Date<Local>, minutes_of_work: i32) -> Result<(), Error> {
-        let mut statement = self.connection.prepare("INSERT INTO dailyTime(startDate, minutesOfWork) VALUES(?,?)")?;
-        statement.execute(params![start.format("%Y-%m-%d").to_string(), minutes_of_work])?;
+    fn get_flex(&self, id: i32) -> Result<FlexLine, Error> {
+        let mut statement = self.connection.prepare("SELECT id, flexMinutes, date, comment FROM flex WHERE id = ?")?;
+        let rows = statement.query(params![id])?;
+        DbConnection::extract_flex_rows(rows)?.pop().ok_or(Error::QueryReturnedNoRows)
+    }
+
+    fn delete_flex(&self, id: i32) -> Result<(), Error> {
+        DbConnection::delete_by_id(&self.connection, "DELETE FROM flex WHERE id=?", id)
+    }
+
+    fn list_all_flex(&self) -> Result<Vec<FlexLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT id, flexMinutes, date, comment FROM flex ORDER BY date")?;
+        let rows = statement.query(NO_PARAMS)?;
+        DbConnection::extract_flex_rows(rows)
+    }
+
+    fn add_daily_time_override(&self, start: &Date<Local>, end: Option<&Date<Local>>, minutes_per_weekday: &[i32; 7]) -> Result<(), Error> {
+        let mut statement = self.connection.prepare("INSERT INTO dailyTime(startDate, endDate, mondayMinutes, tuesdayMinutes, wednesdayMinutes, thursdayMinutes, fridayMinutes, saturdayMinutes, sundayMinutes) VALUES(?,?,?,?,?,?,?,?,?)")?;
+        let minutes = minutes_per_weekday;
+        statement.execute(params![start.format("%Y-%m-%d").to_string(), end.map(|end| end.format("%Y-%m-%d").to_string()),
+                                  minutes[0], minutes[1], minutes[2], minutes[3], minutes[4], minutes[5], minutes[6]])?;
         Ok(())
     }
 
-    pub fn stop_daily_time_override(&self, id: i32, end: &Date<Local>) -> Result<(), Error> {
+    fn stop_daily_time_override(&self, id: i32, end: &Date<Local>) -> Result<(), Error> {
         let mut statement = self.connection.prepare("UPDATE dailyTime SET endDate=? WHERE id=?")?;
         let result = statement.execute(params![end.format("%Y-%m-%d").to_string(), id])?;
         return if result == 0 {
@@ -161,46 +543,107 @@ impl DbConnection {
         }
     }
 
-    pub fn list_daily_time_overrides(&self) -> Result<Vec<DailyTimeOverrideLine>, Error> {
-        let mut statement = self.connection.prepare("SELECT id, startDate, endDate, minutesOfWork FROM dailyTime")?;
-        let mut rows = statement.query(NO_PARAMS)?;
-        let mut daily_times: Vec<DailyTimeOverrideLine> = Vec::new();
-        while let Some(row) = rows.next()? {
-            let start_date: String = row.get(1)?;
-            let end_date: Option<String> = row.get(2)?;
-            daily_times.push(DailyTimeOverrideLine {
-                id: row.get(0)?,
-                start: NaiveDate::parse_from_str(start_date.as_str(), "%Y-%m-%d").expect("Could not parse date from DB."),
-                end: end_date.map(|date| NaiveDate::parse_from_str(date.as_str(), "%Y-%m-%d").expect("Could not parse date from DB.")),
-                minutes_of_work: row.get(3)?
-            })
-        }
-        Ok(daily_times)
+    fn list_daily_time_overrides(&self) -> Result<Vec<DailyTimeOverrideLine>, Error> {
+        let mut statement = self.connection.prepare(format!("SELECT {} FROM dailyTime", DAILY_TIME_COLUMNS).as_str())?;
+        let rows = statement.query(NO_PARAMS)?;
+        DbConnection::extract_daily_time_override_rows(rows)
+    }
+
+    fn get_daily_time_override(&self, id: i32) -> Result<DailyTimeOverrideLine, Error> {
+        let mut statement = self.connection.prepare(format!("SELECT {} FROM dailyTime WHERE id = ?", DAILY_TIME_COLUMNS).as_str())?;
+        let rows = statement.query(params![id])?;
+        DbConnection::extract_daily_time_override_rows(rows)?.pop().ok_or(Error::QueryReturnedNoRows)
+    }
+
+    fn delete_daily_time_override(&self, id: i32) -> Result<(), Error> {
+        DbConnection::delete_by_id(&self.connection, "DELETE FROM dailyTime WHERE id=?", id)
+    }
+
+    fn add_absence(&self, category: AbsenceCategory, start: &Date<Local>, end: &Date<Local>, minutes_per_day: Option<i32>, comment: Option<&str>) -> Result<(), Error> {
+        let mut statement = self.connection.prepare("INSERT INTO absence(category, startDate, endDate, minutesPerDay, comment) VALUES(?,?,?,?,?)")?;
+        statement.execute(params![category.name(), start.format("%Y-%m-%d").to_string(), end.format("%Y-%m-%d").to_string(), minutes_per_day, comment])?;
+        Ok(())
+    }
+
+    fn list_absences(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<AbsenceLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT id, category, startDate, endDate, minutesPerDay, comment FROM absence WHERE startDate < ? AND endDate >= ? ORDER BY startDate")?;
+        let rows = statement.query(params![to.format("%Y-%m-%d").to_string(), from.format("%Y-%m-%d").to_string()])?;
+        DbConnection::extract_absence_rows(rows)
+    }
+
+    fn get_absence(&self, id: i32) -> Result<AbsenceLine, Error> {
+        let mut statement = self.connection.prepare("SELECT id, category, startDate, endDate, minutesPerDay, comment FROM absence WHERE id = ?")?;
+        let rows = statement.query(params![id])?;
+        DbConnection::extract_absence_rows(rows)?.pop().ok_or(Error::QueryReturnedNoRows)
+    }
+
+    fn delete_absence(&self, id: i32) -> Result<(), Error> {
+        DbConnection::delete_by_id(&self.connection, "DELETE FROM absence WHERE id=?", id)
+    }
+
+    fn list_all_absences(&self) -> Result<Vec<AbsenceLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT id, category, startDate, endDate, minutesPerDay, comment FROM absence ORDER BY startDate")?;
+        let rows = statement.query(NO_PARAMS)?;
+        DbConnection::extract_absence_rows(rows)
+    }
+
+    fn set_absence_allowance(&self, category: AbsenceCategory, year: i32, days: f64) -> Result<(), Error> {
+        self.connection.execute("INSERT OR REPLACE INTO absenceAllowance(category, year, days) VALUES(?,?,?)", params![category.name(), year, days])?;
+        Ok(())
     }
 
-    pub fn get_daily_time_override_for_date(&self, date: &Date<Local>) -> Result<Option<DailyTimeOverrideLine>, Error> {
-        let mut statement = self.connection.prepare("SELECT id, startDate, endDate, minutesOfWork FROM dailyTime WHERE ? >= dailyTime.startDate AND (dailyTime.endDate IS NULL OR ? < dailyTime.endDate)")?;
-        let date_string = date.format("%Y-%m-%d").to_string();
-        let mut rows = statement.query(params![date_string, date_string])?;
-        match rows.next()? {
-            Some(row) => {
-                let start_date: String = row.get(1)?;
-                let end_date: Option<String> = row.get(2)?;
-                Ok(Some(DailyTimeOverrideLine {
-                    id: row.get(0)?,
-                    start: NaiveDate::parse_from_str(start_date.as_str(), "%Y-%m-%d").expect("Could not parse date from DB."),
-                    end: end_date.map(|date| NaiveDate::parse_from_str(date.as_str(), "%Y-%m-%d").expect("Could not parse date from DB.")),
-                    minutes_of_work: row.get(3)?
-                }))
+    fn list_absence_allowances(&self, year: i32) -> Result<Vec<AbsenceAllowanceLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT category, year, days FROM absenceAllowance WHERE year = ? ORDER BY category")?;
+        let rows = statement.query(params![year])?;
+        DbConnection::extract_absence_allowance_rows(rows)
+    }
+
+    fn list_all_absence_allowances(&self) -> Result<Vec<AbsenceAllowanceLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT category, year, days FROM absenceAllowance ORDER BY year, category")?;
+        let rows = statement.query(NO_PARAMS)?;
+        DbConnection::extract_absence_allowance_rows(rows)
+    }
+
+    fn add_holidays(&self, holidays: &[(NaiveDate, String)]) -> Result<usize, Error> {
+        self.in_transaction(|db| {
+            let mut statement = db.connection.prepare("INSERT OR IGNORE INTO holiday(date, name) VALUES(?,?)")?;
+            let mut added = 0;
+            for (date, name) in holidays {
+                added += statement.execute(params![date.format("%Y-%m-%d").to_string(), name])?;
             }
-            _ => Ok(None)
+            Ok(added)
+        })
+    }
+
+    fn list_holidays(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<HolidayLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT date, name FROM holiday WHERE date >= ? AND date < ? ORDER BY date")?;
+        let rows = statement.query(params![from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string()])?;
+        DbConnection::extract_holiday_rows(rows)
+    }
+
+    fn list_all_holidays(&self) -> Result<Vec<HolidayLine>, Error> {
+        let mut statement = self.connection.prepare("SELECT date, name FROM holiday ORDER BY date")?;
+        let rows = statement.query(NO_PARAMS)?;
+        DbConnection::extract_holiday_rows(rows)
+    }
+
+    fn delete_holiday(&self, date: &Date<Local>) -> Result<(), Error> {
+        let result = self.connection.execute("DELETE FROM holiday WHERE date=?", params![date.format("%Y-%m-%d").to_string()])?;
+        if result == 0 {
+            Err(Error::QueryReturnedNoRows)
+        } else {
+            Ok(())
         }
     }
 
-    pub fn clear(&self) {
-        self.connection.execute("DELETE FROM time", NO_PARAMS).unwrap();
-        self.connection.execute("DELETE FROM flex", NO_PARAMS).unwrap();
-        self.connection.execute("DELETE FROM dailyTime", NO_PARAMS).unwrap();
+    fn delete_all_data(&self) -> Result<(), Error> {
+        self.connection.execute_batch("DELETE FROM time;\
+            DELETE FROM flex;\
+            DELETE FROM dailyTime;\
+            DELETE FROM absence;\
+            DELETE FROM absenceAllowance;\
+            DELETE FROM holiday;\
+            DELETE FROM runningTimer;")
     }
 
 }
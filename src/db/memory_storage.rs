@@ -0,0 +1,258 @@
+use crate::clock::Clock;
+use crate::db::db_manager::{DateLine, FlexLine, DailyTimeOverrideLine, AbsenceCategory, AbsenceLine, AbsenceAllowanceLine, HolidayLine, MIGRATIONS};
+use crate::db::storage::Storage;
+use chrono::{DateTime, Local, TimeZone, Date, NaiveDate};
+use rusqlite::Error;
+use std::cell::RefCell;
+
+/// Keeps all entries in memory, e.g. for tests that should not share a DB file. Behaves like the SQLite storage,
+/// including that times are stored with second precision and that ids are one more than the highest existing id.
+pub struct MemoryStorage {
+    data: RefCell<Data>,
+    clock: Clock
+}
+
+#[derive(Clone, Default)]
+struct Data {
+    times: Vec<DateLine>,
+    flex: Vec<FlexLine>,
+    daily_time_overrides: Vec<DailyTimeOverrideLine>,
+    absences: Vec<AbsenceLine>,
+    absence_allowances: Vec<AbsenceAllowanceLine>,
+    holidays: Vec<HolidayLine>,
+    running_timer: Option<DateTime<Local>>
+}
+
+impl MemoryStorage {
+    pub fn new(clock: Clock) -> MemoryStorage {
+        MemoryStorage { data: RefCell::new(Data::default()), clock }
+    }
+}
+
+fn to_seconds(time: &DateTime<Local>) -> DateTime<Local> {
+    Local.timestamp(time.timestamp(), 0)
+}
+
+fn next_id<T>(lines: &[T], id: fn(&T) -> i32) -> i32 {
+    lines.iter().map(id).max().unwrap_or(0) + 1
+}
+
+fn find<T: Clone>(lines: &[T], matches: impl Fn(&T) -> bool) -> Result<T, Error> {
+    lines.iter().find(|line| matches(line)).cloned().ok_or(Error::QueryReturnedNoRows)
+}
+
+fn remove<T>(lines: &mut Vec<T>, matches: impl Fn(&T) -> bool) -> Result<(), Error> {
+    let count = lines.len();
+    lines.retain(|line| !matches(line));
+    if lines.len() == count {
+        Err(Error::QueryReturnedNoRows)
+    } else {
+        Ok(())
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    fn get_version(&self) -> Result<i8, Error> {
+        Ok(MIGRATIONS.len() as i8 + 1)
+    }
+
+    fn transaction(&self, action: &mut dyn FnMut(&dyn Storage) -> Result<(), Error>) -> Result<(), Error> {
+        let snapshot = self.data.borrow().clone();
+        let result = action(self);
+        if result.is_err() {
+            *self.data.borrow_mut() = snapshot;
+        }
+        result
+    }
+
+    fn insert_time(&self, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), Error> {
+        let mut data = self.data.borrow_mut();
+        let id = next_id(&data.times, |line| line.id);
+        data.times.push(DateLine { id, start: to_seconds(start), end: to_seconds(end), break_time_minutes, date: start.naive_local().date() });
+        Ok(())
+    }
+
+    fn update_time(&self, id: i32, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), Error> {
+        let mut data = self.data.borrow_mut();
+        let line = data.times.iter_mut().find(|line| line.id == id).ok_or(Error::QueryReturnedNoRows)?;
+        *line = DateLine { id, start: to_seconds(start), end: to_seconds(end), break_time_minutes, date: start.naive_local().date() };
+        Ok(())
+    }
+
+    fn get_time(&self, id: i32) -> Result<DateLine, Error> {
+        find(&self.data.borrow().times, |line| line.id == id)
+    }
+
+    fn delete_time(&self, id: i32) -> Result<(), Error> {
+        remove(&mut self.data.borrow_mut().times, |line| line.id == id)
+    }
+
+    fn get_num_time_entries(&self) -> Result<i32, Error> {
+        let now = self.clock.now();
+        let data = self.data.borrow();
+        let started = data.times.iter().filter(|line| line.start < now).count() + data.running_timer.iter().filter(|start| **start < now).count();
+        Ok(started as i32)
+    }
+
+    fn get_date_for_last_entry(&self) -> Result<NaiveDate, Error> {
+        let now = self.clock.now();
+        let data = self.data.borrow();
+        let last_entry = data.times.iter().filter(|line| line.start < now).max_by_key(|line| line.start).map(|line| line.date);
+        let running_timer = data.running_timer.map(|start| start.naive_local().date());
+        last_entry.max(running_timer).ok_or(Error::QueryReturnedNoRows)
+    }
+
+    fn start_timer(&self, start: &DateTime<Local>) -> Result<(), Error> {
+        self.data.borrow_mut().running_timer = Some(to_seconds(start));
+        Ok(())
+    }
+
+    fn get_running_timer(&self) -> Result<Option<DateTime<Local>>, Error> {
+        Ok(self.data.borrow().running_timer)
+    }
+
+    fn stop_timer(&self, end: &DateTime<Local>, break_time_minutes: i32) -> Result<DateTime<Local>, Error> {
+        let start = self.data.borrow_mut().running_timer.take().ok_or(Error::QueryReturnedNoRows)?;
+        self.insert_time(&start, end, break_time_minutes)?;
+        Ok(start)
+    }
+
+    fn list_times(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<DateLine>, Error> {
+        Ok(self.list_all_times()?.into_iter().filter(|line| line.start > *from && line.end < *to).collect())
+    }
+
+    fn list_all_times(&self) -> Result<Vec<DateLine>, Error> {
+        let mut times = self.data.borrow().times.clone();
+        times.sort_by_key(|line| line.start);
+        Ok(times)
+    }
+
+    fn add_flex(&self, flex_minutes: i32, date: &Date<Local>, comment: Option<&str>) -> Result<(), Error> {
+        let mut data = self.data.borrow_mut();
+        let id = next_id(&data.flex, |line| line.id);
+        data.flex.push(FlexLine { id, date: date.and_hms(0, 0, 0), flex_minutes, comment: comment.unwrap_or_default().to_string() });
+        Ok(())
+    }
+
+    fn list_flex(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<FlexLine>, Error> {
+        Ok(self.list_all_flex()?.into_iter().filter(|line| line.date >= *from && line.date < *to).collect())
+    }
+
+    fn get_flex(&self, id: i32) -> Result<FlexLine, Error> {
+        find(&self.data.borrow().flex, |line| line.id == id)
+    }
+
+    fn delete_flex(&self, id: i32) -> Result<(), Error> {
+        remove(&mut self.data.borrow_mut().flex, |line| line.id == id)
+    }
+
+    fn list_all_flex(&self) -> Result<Vec<FlexLine>, Error> {
+        let mut flex = self.data.borrow().flex.clone();
+        flex.sort_by_key(|line| line.date);
+        Ok(flex)
+    }
+
+    fn add_daily_time_override(&self, start: &Date<Local>, end: Option<&Date<Local>>, minutes_per_weekday: &[i32; 7]) -> Result<(), Error> {
+        let mut data = self.data.borrow_mut();
+        let id = next_id(&data.daily_time_overrides, |line| line.id);
+        data.daily_time_overrides.push(DailyTimeOverrideLine { id, start: start.naive_local(), end: end.map(Date::naive_local), minutes_per_weekday: *minutes_per_weekday });
+        Ok(())
+    }
+
+    fn stop_daily_time_override(&self, id: i32, end: &Date<Local>) -> Result<(), Error> {
+        let mut data = self.data.borrow_mut();
+        let line = data.daily_time_overrides.iter_mut().find(|line| line.id == id).ok_or(Error::QueryReturnedNoRows)?;
+        line.end = Some(end.naive_local());
+        Ok(())
+    }
+
+    fn list_daily_time_overrides(&self) -> Result<Vec<DailyTimeOverrideLine>, Error> {
+        Ok(self.data.borrow().daily_time_overrides.clone())
+    }
+
+    fn get_daily_time_override(&self, id: i32) -> Result<DailyTimeOverrideLine, Error> {
+        find(&self.data.borrow().daily_time_overrides, |line| line.id == id)
+    }
+
+    fn delete_daily_time_override(&self, id: i32) -> Result<(), Error> {
+        remove(&mut self.data.borrow_mut().daily_time_overrides, |line| line.id == id)
+    }
+
+    fn add_absence(&self, category: AbsenceCategory, start: &Date<Local>, end: &Date<Local>, minutes_per_day: Option<i32>, comment: Option<&str>) -> Result<(), Error> {
+        let mut data = self.data.borrow_mut();
+        let id = next_id(&data.absences, |line| line.id);
+        data.absences.push(AbsenceLine { id, category, start: start.naive_local(), end: end.naive_local(), minutes_per_day, comment: comment.unwrap_or_default().to_string() });
+        Ok(())
+    }
+
+    fn list_absences(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<AbsenceLine>, Error> {
+        let (from, to) = (from.naive_local().date(), to.naive_local().date());
+        Ok(self.list_all_absences()?.into_iter().filter(|line| line.start < to && line.end >= from).collect())
+    }
+
+    fn get_absence(&self, id: i32) -> Result<AbsenceLine, Error> {
+        find(&self.data.borrow().absences, |line| line.id == id)
+    }
+
+    fn delete_absence(&self, id: i32) -> Result<(), Error> {
+        remove(&mut self.data.borrow_mut().absences, |line| line.id == id)
+    }
+
+    fn list_all_absences(&self) -> Result<Vec<AbsenceLine>, Error> {
+        let mut absences = self.data.borrow().absences.clone();
+        absences.sort_by_key(|line| line.start);
+        Ok(absences)
+    }
+
+    fn set_absence_allowance(&self, category: AbsenceCategory, year: i32, days: f64) -> Result<(), Error> {
+        let mut data = self.data.borrow_mut();
+        data.absence_allowances.retain(|line| line.category != category || line.year != year);
+        data.absence_allowances.push(AbsenceAllowanceLine { category, year, days });
+        Ok(())
+    }
+
+    fn list_absence_allowances(&self, year: i32) -> Result<Vec<AbsenceAllowanceLine>, Error> {
+        Ok(self.list_all_absence_allowances()?.into_iter().filter(|line| line.year == year).collect())
+    }
+
+    fn list_all_absence_allowances(&self) -> Result<Vec<AbsenceAllowanceLine>, Error> {
+        let mut allowances = self.data.borrow().absence_allowances.clone();
+        allowances.sort_by_key(|line| (line.year, line.category.name()));
+        Ok(allowances)
+    }
+
+    fn add_holidays(&self, holidays: &[(NaiveDate, String)]) -> Result<usize, Error> {
+        let mut data = self.data.borrow_mut();
+        let mut added = 0;
+        for (date, name) in holidays {
+            if !data.holidays.iter().any(|line| line.date == *date) {
+                data.holidays.push(HolidayLine { date: *date, name: name.clone() });
+                added += 1;
+            }
+        }
+        data.holidays.sort_by_key(|line| line.date);
+        Ok(added)
+    }
+
+    fn list_holidays(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<HolidayLine>, Error> {
+        let (from, to) = (from.naive_local().date(), to.naive_local().date());
+        Ok(self.data.borrow().holidays.iter().filter(|line| line.date >= from && line.date < to).cloned().collect())
+    }
+
+    fn list_all_holidays(&self) -> Result<Vec<HolidayLine>, Error> {
+        Ok(self.data.borrow().holidays.clone())
+    }
+
+    fn delete_holiday(&self, date: &Date<Local>) -> Result<(), Error> {
+        remove(&mut self.data.borrow_mut().holidays, |line| line.date == date.naive_local())
+    }
+
+    fn delete_all_data(&self) -> Result<(), Error> {
+        *self.data.borrow_mut() = Data::default();
+        Ok(())
+    }
+}
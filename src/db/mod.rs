@@ -1 +1,3 @@
-pub mod db_manager;
\ No newline at end of file
+pub mod db_manager;
+pub mod storage;
+pub mod memory_storage;
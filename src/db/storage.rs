@@ -0,0 +1,117 @@
+use crate::clock::Clock;
+use crate::db::db_manager::{DateLine, FlexLine, DailyTimeOverrideLine, AbsenceCategory, AbsenceLine, AbsenceAllowanceLine, HolidayLine};
+use chrono::{DateTime, Local, Date, NaiveDate};
+use rusqlite::Error;
+
+/// Where the entries are stored. `DbConnection` stores them in SQLite and `MemoryStorage` keeps them in memory, e.g. for tests.
+/// Methods that work on a single entry return `Error::QueryReturnedNoRows` if there is no entry with the id or date.
+pub trait Storage {
+    /// The clock that decides which entries have started.
+    fn clock(&self) -> &Clock;
+
+    /// The schema version of the stored data.
+    fn get_version(&self) -> Result<i8, Error>;
+
+    /// Runs `action` so that either all or none of its changes are stored. Use `in_transaction` on `dyn Storage` to
+    /// return a value from the action.
+    fn transaction(&self, action: &mut dyn FnMut(&dyn Storage) -> Result<(), Error>) -> Result<(), Error>;
+
+    fn insert_time(&self, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), Error>;
+
+    fn update_time(&self, id: i32, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), Error>;
+
+    fn get_time(&self, id: i32) -> Result<DateLine, Error>;
+
+    fn delete_time(&self, id: i32) -> Result<(), Error>;
+
+    /// Number of time entries that have started, including a running timer.
+    fn get_num_time_entries(&self) -> Result<i32, Error>;
+
+    /// The date of the last time entry that has started. A running timer counts as an entry for the day it was started.
+    fn get_date_for_last_entry(&self) -> Result<NaiveDate, Error>;
+
+    fn start_timer(&self, start: &DateTime<Local>) -> Result<(), Error>;
+
+    fn get_running_timer(&self) -> Result<Option<DateTime<Local>>, Error>;
+
+    /// Turns the running timer into a time entry ending at `end`. Returns when the timer was started.
+    fn stop_timer(&self, end: &DateTime<Local>, break_time_minutes: i32) -> Result<DateTime<Local>, Error>;
+
+    /// Lists the time entries that start after `from` and end before `to`, ordered by start.
+    fn list_times(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<DateLine>, Error>;
+
+    fn list_all_times(&self) -> Result<Vec<DateLine>, Error>;
+
+    fn add_flex(&self, flex_minutes: i32, date: &Date<Local>, comment: Option<&str>) -> Result<(), Error>;
+
+    /// Lists the flex entries from `from` (inclusive) to `to` (exclusive), ordered by date.
+    fn list_flex(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<FlexLine>, Error>;
+
+    fn get_flex(&self, id: i32) -> Result<FlexLine, Error>;
+
+    fn delete_flex(&self, id: i32) -> Result<(), Error>;
+
+    fn list_all_flex(&self) -> Result<Vec<FlexLine>, Error>;
+
+    /// `minutes_per_weekday` starts with Monday. The end date is exclusive.
+    fn add_daily_time_override(&self, start: &Date<Local>, end: Option<&Date<Local>>, minutes_per_weekday: &[i32; 7]) -> Result<(), Error>;
+
+    fn stop_daily_time_override(&self, id: i32, end: &Date<Local>) -> Result<(), Error>;
+
+    /// Lists all daily time overrides in the order they were added.
+    fn list_daily_time_overrides(&self) -> Result<Vec<DailyTimeOverrideLine>, Error>;
+
+    fn get_daily_time_override(&self, id: i32) -> Result<DailyTimeOverrideLine, Error>;
+
+    fn delete_daily_time_override(&self, id: i32) -> Result<(), Error>;
+
+    fn add_absence(&self, category: AbsenceCategory, start: &Date<Local>, end: &Date<Local>, minutes_per_day: Option<i32>, comment: Option<&str>) -> Result<(), Error>;
+
+    /// Lists all absences that overlap the period from `from` (inclusive) to `to` (exclusive), ordered by start date.
+    fn list_absences(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<AbsenceLine>, Error>;
+
+    fn get_absence(&self, id: i32) -> Result<AbsenceLine, Error>;
+
+    fn delete_absence(&self, id: i32) -> Result<(), Error>;
+
+    fn list_all_absences(&self) -> Result<Vec<AbsenceLine>, Error>;
+
+    /// Sets the allowance of the category for the year, replacing an existing one.
+    fn set_absence_allowance(&self, category: AbsenceCategory, year: i32, days: f64) -> Result<(), Error>;
+
+    /// Lists the allowances of the year, ordered by category name.
+    fn list_absence_allowances(&self, year: i32) -> Result<Vec<AbsenceAllowanceLine>, Error>;
+
+    fn list_all_absence_allowances(&self) -> Result<Vec<AbsenceAllowanceLine>, Error>;
+
+    /// Adds the holidays for the dates that do not already have one. Returns the number of added holidays.
+    fn add_holidays(&self, holidays: &[(NaiveDate, String)]) -> Result<usize, Error>;
+
+    /// Lists the holidays from `from` (inclusive) to `to` (exclusive), ordered by date.
+    fn list_holidays(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<HolidayLine>, Error>;
+
+    fn list_all_holidays(&self) -> Result<Vec<HolidayLine>, Error>;
+
+    fn delete_holiday(&self, date: &Date<Local>) -> Result<(), Error>;
+
+    /// Deletes all entries but keeps the schema version.
+    fn delete_all_data(&self) -> Result<(), Error>;
+}
+
+impl dyn Storage + '_ {
+    /// Runs `action` in a transaction, see `Storage::transaction`, and returns its value.
+    pub fn in_transaction<T, F>(&self, action: F) -> Result<T, Error>
+        where
+            F: FnOnce(&dyn Storage) -> Result<T, Error>
+    {
+        // The errors are only reachable if an implementation does not run the action exactly once.
+        let mut action = Some(action);
+        let mut value = None;
+        self.transaction(&mut |storage| {
+            let action = action.take().ok_or(Error::InvalidQuery)?;
+            value = Some(action(storage)?);
+            Ok(())
+        })?;
+        value.ok_or(Error::InvalidQuery)
+    }
+}
@@ -0,0 +1,241 @@
+use crate::db::db_manager::AbsenceCategory;
+use crate::db::storage::Storage;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Date};
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::hash::Hash;
+use crate::error::NpttError;
+
+/// Version of the dump document. Increase it when the layout changes in a way that older versions cannot read,
+/// and keep reading the older versions.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// All data in the DB. Dates are `YYYY-MM-DD` and times are RFC 3339, so the document does not depend on the SQLite schema.
+#[derive(Serialize, Deserialize)]
+pub struct Dump {
+    pub format_version: u32,
+    /// The schema version of the DB that was exported. Only informational.
+    pub db_version: i8,
+    pub time: Vec<TimeEntry>,
+    pub flex: Vec<FlexEntry>,
+    pub daily_time_overrides: Vec<DailyTimeOverrideEntry>,
+    #[serde(default)]
+    pub absences: Vec<AbsenceEntry>,
+    #[serde(default)]
+    pub absence_allowances: Vec<AbsenceAllowanceEntry>,
+    #[serde(default)]
+    pub holidays: Vec<HolidayEntry>,
+    #[serde(default)]
+    pub running_timer: Option<String>
+}
+
+impl Dump {
+    /// The number of entries of every kind of data, with the names used by `ImportCount`.
+    pub fn counts(&self) -> Vec<(&'static str, usize)> {
+        vec![
+            ("time entries", self.time.len()),
+            ("flex entries", self.flex.len()),
+            ("daily time overrides", self.daily_time_overrides.len()),
+            ("absences", self.absences.len()),
+            ("absence allowances", self.absence_allowances.len()),
+            ("holidays", self.holidays.len()),
+            ("running timers", self.running_timer.iter().count())
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub start: String,
+    pub end: String,
+    pub break_minutes: i32
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FlexEntry {
+    pub date: String,
+    pub flex_minutes: i32,
+    pub comment: Option<String>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DailyTimeOverrideEntry {
+    pub start: String,
+    pub end: Option<String>,
+    /// Starts with Monday.
+    pub minutes_per_weekday: [i32; 7]
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AbsenceEntry {
+    pub category: String,
+    pub start: String,
+    pub end: String,
+    pub minutes_per_day: Option<i32>,
+    pub comment: Option<String>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AbsenceAllowanceEntry {
+    pub category: String,
+    pub year: i32,
+    pub days: f64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HolidayEntry {
+    pub date: String,
+    pub name: String
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImportMode {
+    /// Keep the existing data and add the entries that do not already exist.
+    Merge,
+    /// Delete all existing data first.
+    Replace
+}
+
+/// The number of added entries and skipped duplicates of one kind of data.
+pub struct ImportCount {
+    pub name: &'static str,
+    pub added: usize,
+    pub duplicates: usize
+}
+
+pub fn export(storage: &dyn Storage) -> Result<Dump, rusqlite::Error> {
+    Ok(Dump {
+        format_version: FORMAT_VERSION,
+        db_version: storage.get_version()?,
+        time: storage.list_all_times()?.into_iter()
+            .map(|line| TimeEntry { start: line.start.to_rfc3339(), end: line.end.to_rfc3339(), break_minutes: line.break_time_minutes })
+            .collect(),
+        flex: storage.list_all_flex()?.into_iter()
+            .map(|line| FlexEntry { date: format_date(&line.date.naive_local().date()), flex_minutes: line.flex_minutes, comment: Some(line.comment).filter(|comment| !comment.is_empty()) })
+            .collect(),
+        daily_time_overrides: storage.list_daily_time_overrides()?.into_iter()
+            .map(|line| DailyTimeOverrideEntry { start: format_date(&line.start), end: line.end.as_ref().map(format_date), minutes_per_weekday: line.minutes_per_weekday })
+            .collect(),
+        absences: storage.list_all_absences()?.into_iter()
+            .map(|line| AbsenceEntry {
+                category: line.category.name().to_string(),
+                start: format_date(&line.start),
+                end: format_date(&line.end),
+                minutes_per_day: line.minutes_per_day,
+                comment: Some(line.comment).filter(|comment| !comment.is_empty())
+            })
+            .collect(),
+        absence_allowances: storage.list_all_absence_allowances()?.into_iter()
+            .map(|line| AbsenceAllowanceEntry { category: line.category.name().to_string(), year: line.year, days: line.days })
+            .collect(),
+        holidays: storage.list_all_holidays()?.into_iter()
+            .map(|line| HolidayEntry { date: format_date(&line.date), name: line.name })
+            .collect(),
+        running_timer: storage.get_running_timer()?.map(|start| start.to_rfc3339())
+    })
+}
+
+/// Imports everything in the dump in a single transaction. An entry that is equal to an existing entry, or to an earlier
+/// entry in the dump, is a duplicate and is skipped. For absence allowances and holidays only the category and year
+/// respectively the date are compared, the existing value is kept. A running timer is only imported if none is running.
+pub fn import(storage: &dyn Storage, dump: &Dump, mode: ImportMode) -> Result<Vec<ImportCount>, NpttError> {
+    if dump.format_version > FORMAT_VERSION {
+        return Err(NpttError::Validation(format!("The dump has format version {} which is newer than the supported version {}. Upgrade the tool first.", dump.format_version, FORMAT_VERSION)));
+    }
+    let time = dump.time.iter()
+        .map(|entry| Ok((parse_time(&entry.start)?, parse_time(&entry.end)?, entry.break_minutes)))
+        .collect::<Result<Vec<_>, NpttError>>()?;
+    let flex = dump.flex.iter()
+        .map(|entry| Ok((parse_local_date(&entry.date)?, entry.flex_minutes, entry.comment.clone().unwrap_or_default())))
+        .collect::<Result<Vec<_>, NpttError>>()?;
+    let overrides = dump.daily_time_overrides.iter()
+        .map(|entry| Ok((parse_local_date(&entry.start)?, entry.end.as_ref().map(|end| parse_local_date(end)).transpose()?, entry.minutes_per_weekday)))
+        .collect::<Result<Vec<_>, NpttError>>()?;
+    let absences = dump.absences.iter()
+        .map(|entry| Ok((parse_category(&entry.category)?, parse_local_date(&entry.start)?, parse_local_date(&entry.end)?, entry.minutes_per_day, entry.comment.clone().unwrap_or_default())))
+        .collect::<Result<Vec<_>, NpttError>>()?;
+    let allowances = dump.absence_allowances.iter()
+        .map(|entry| Ok((parse_category(&entry.category)?, entry.year, entry.days)))
+        .collect::<Result<Vec<_>, NpttError>>()?;
+    let holidays = dump.holidays.iter()
+        .map(|entry| Ok((parse_date(&entry.date)?, entry.name.clone())))
+        .collect::<Result<Vec<_>, NpttError>>()?;
+    let running_timer = dump.running_timer.as_ref().map(|start| parse_time(start)).transpose()?;
+
+    storage.in_transaction(|db| {
+        if mode == ImportMode::Replace {
+            db.delete_all_data()?;
+        }
+        let mut counts = Vec::new();
+
+        let mut existing: HashSet<(i64, i64)> = db.list_all_times()?.iter().map(|line| (line.start.timestamp(), line.end.timestamp())).collect();
+        counts.push(import_new("time entries", &time, &mut existing, |(start, end, _)| (start.timestamp(), end.timestamp()),
+                               |(start, end, break_minutes)| db.insert_time(start, end, *break_minutes))?);
+
+        let mut existing: HashSet<(NaiveDate, i32, String)> = db.list_all_flex()?.into_iter().map(|line| (line.date.naive_local().date(), line.flex_minutes, line.comment)).collect();
+        counts.push(import_new("flex entries", &flex, &mut existing, |(date, minutes, comment)| (date.naive_local(), *minutes, comment.clone()),
+                               |(date, minutes, comment)| db.add_flex(*minutes, date, Some(comment.as_str()).filter(|comment| !comment.is_empty())))?);
+
+        let mut existing: HashSet<(NaiveDate, Option<NaiveDate>, [i32; 7])> = db.list_daily_time_overrides()?.into_iter().map(|line| (line.start, line.end, line.minutes_per_weekday)).collect();
+        counts.push(import_new("daily time overrides", &overrides, &mut existing, |(start, end, minutes)| (start.naive_local(), end.map(|end| end.naive_local()), *minutes),
+                               |(start, end, minutes)| db.add_daily_time_override(start, end.as_ref(), minutes))?);
+
+        let mut existing: HashSet<(AbsenceCategory, NaiveDate, NaiveDate, Option<i32>, String)> = db.list_all_absences()?.into_iter()
+            .map(|line| (line.category, line.start, line.end, line.minutes_per_day, line.comment)).collect();
+        counts.push(import_new("absences", &absences, &mut existing, |(category, start, end, minutes, comment)| (*category, start.naive_local(), end.naive_local(), *minutes, comment.clone()),
+                               |(category, start, end, minutes, comment)| db.add_absence(*category, start, end, *minutes, Some(comment.as_str()).filter(|comment| !comment.is_empty())))?);
+
+        let mut existing: HashSet<(AbsenceCategory, i32)> = db.list_all_absence_allowances()?.into_iter().map(|line| (line.category, line.year)).collect();
+        counts.push(import_new("absence allowances", &allowances, &mut existing, |(category, year, _)| (*category, *year),
+                               |(category, year, days)| db.set_absence_allowance(*category, *year, *days))?);
+
+        let mut existing: HashSet<NaiveDate> = db.list_all_holidays()?.into_iter().map(|line| line.date).collect();
+        counts.push(import_new("holidays", &holidays, &mut existing, |(date, _)| *date,
+                               |(date, name)| db.add_holidays(&[(*date, name.clone())]).map(|_| ()))?);
+
+        let mut existing: HashSet<()> = db.get_running_timer()?.map(|_| ()).into_iter().collect();
+        counts.push(import_new("running timers", &running_timer.into_iter().collect::<Vec<DateTime<Local>>>(), &mut existing, |_| (),
+                               |start| db.start_timer(start))?);
+        Ok(counts)
+    }).map_err(NpttError::db("Could not import, nothing was imported"))
+}
+
+fn import_new<T, K, F, I>(name: &'static str, entries: &[T], existing: &mut HashSet<K>, key: F, mut insert: I) -> Result<ImportCount, rusqlite::Error>
+    where
+        K: Eq + Hash,
+        F: Fn(&T) -> K,
+        I: FnMut(&T) -> Result<(), rusqlite::Error>
+{
+    let mut count = ImportCount { name, added: 0, duplicates: 0 };
+    for entry in entries {
+        if existing.insert(key(entry)) {
+            insert(entry)?;
+            count.added += 1;
+        } else {
+            count.duplicates += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn format_date(date: &NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// A date of the dump in the local time zone. Fails for a date without a midnight in the local time zone.
+fn parse_local_date(date: &str) -> Result<Date<Local>, NpttError> {
+    Local.from_local_date(&parse_date(date)?).single()
+        .ok_or_else(|| NpttError::Validation(format!("The date {} does not exist in the local time zone.", date)))
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, NpttError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| NpttError::Parse(format!("'{}' is not a date in the format YYYY-MM-DD.", date)))
+}
+
+fn parse_time(time: &str) -> Result<DateTime<Local>, NpttError> {
+    DateTime::parse_from_rfc3339(time).map(|time| time.with_timezone(&Local)).map_err(|_| NpttError::Parse(format!("'{}' is not an RFC 3339 time.", time)))
+}
+
+fn parse_category(category: &str) -> Result<AbsenceCategory, NpttError> {
+    AbsenceCategory::from_name(category).ok_or_else(|| NpttError::Parse(format!("'{}' is not an absence category.", category)))
+}
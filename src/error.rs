@@ -0,0 +1,66 @@
+use crate::db::db_manager::DbInitError;
+use std::{error, fmt, io};
+
+/// Every error that is reported to the user. Each category exits the process with its own code, see `exit_code`.
+#[derive(Debug)]
+pub enum NpttError {
+    /// A DB operation failed. The first value describes what was being done.
+    Db(String, rusqlite::Error),
+    /// The DB could not be opened or migrated.
+    DbInit(DbInitError),
+    /// An argument or a file could not be parsed.
+    Parse(String),
+    /// The input was understood but cannot be used, e.g. an end before the start or an id that does not exist.
+    Validation(String),
+    /// Reading or writing a file or the terminal failed. The first value describes what was being done.
+    Io(String, io::Error)
+}
+
+impl NpttError {
+    /// Maps a DB error to `NpttError::Db`, for use with `map_err`.
+    pub fn db(context: &str) -> impl FnOnce(rusqlite::Error) -> NpttError + '_ {
+        move |err| NpttError::Db(context.to_string(), err)
+    }
+
+    /// Maps an IO error to `NpttError::Io`, for use with `map_err`.
+    pub fn io(context: String) -> impl FnOnce(io::Error) -> NpttError {
+        move |err| NpttError::Io(context, err)
+    }
+
+    /// Maps a "no rows" error to a validation error saying that there is no `kind` with the id.
+    pub fn not_found(kind: &str, id: i32) -> impl FnOnce(rusqlite::Error) -> NpttError + '_ {
+        move |err| match err {
+            rusqlite::Error::QueryReturnedNoRows => NpttError::Validation(format!("There is no {} with id {}.", kind, id)),
+            _ => NpttError::Db(format!("Could not fetch {}", kind), err)
+        }
+    }
+
+    /// The process exit code. 1 is also used by clap for invalid arguments.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            NpttError::Validation(_) => 1,
+            NpttError::Parse(_) => 2,
+            NpttError::Io(_, _) => 3,
+            NpttError::Db(_, _) | NpttError::DbInit(_) => 4
+        }
+    }
+}
+
+impl fmt::Display for NpttError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NpttError::Db(context, err) => write!(f, "{}: {}", context, err),
+            NpttError::DbInit(err) => write!(f, "Could not connect to db: {}", err),
+            NpttError::Parse(message) | NpttError::Validation(message) => write!(f, "{}", message),
+            NpttError::Io(context, err) => write!(f, "{}: {}", context, err)
+        }
+    }
+}
+
+impl error::Error for NpttError {}
+
+impl From<DbInitError> for NpttError {
+    fn from(err: DbInitError) -> Self {
+        NpttError::DbInit(err)
+    }
+}
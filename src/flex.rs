@@ -0,0 +1,164 @@
+use crate::db::db_manager::{DateLine, FlexLine, DailyTimeOverrideLine, AbsenceLine, HolidayLine};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Datelike, Duration};
+use crate::db::storage::Storage;
+use rusqlite::Error;
+use std::collections::btree_map::BTreeMap;
+use std::collections::HashSet;
+
+/// Time entries can span midnight, but are expected to be shorter than this many days.
+pub const MAX_ENTRY_DAYS: i64 = 7;
+
+/// Which day the time of an entry that spans midnight belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MidnightAttribution {
+    /// The whole entry belongs to the day it started.
+    Start,
+    /// The whole entry belongs to the day it ended.
+    End,
+    /// The entry is split at midnight and each day gets its part. The break belongs to the first day.
+    Split
+}
+
+impl MidnightAttribution {
+    pub const NAMES: [&'static str; 3] = ["start", "end", "split"];
+
+    pub fn from_name(name: &str) -> Option<MidnightAttribution> {
+        match name {
+            "start" => Some(MidnightAttribution::Start),
+            "end" => Some(MidnightAttribution::End),
+            "split" => Some(MidnightAttribution::Split),
+            _ => None
+        }
+    }
+}
+
+/// Sets the date of every row to the day it belongs to. With `Split` rows that span midnight become one row per day.
+/// An entry that ends at midnight belongs to the day before.
+pub fn attribute_to_days(rows: Vec<DateLine>, attribution: MidnightAttribution) -> Vec<DateLine> {
+    let mut attributed = Vec::new();
+    for row in rows {
+        let first_day = row.start.date();
+        let last_day = (row.end - Duration::seconds(1)).date().max(first_day);
+        match attribution {
+            MidnightAttribution::Start => attributed.push(DateLine { date: first_day.naive_local(), ..row }),
+            MidnightAttribution::End => attributed.push(DateLine { date: last_day.naive_local(), ..row }),
+            MidnightAttribution::Split => {
+                let (mut day, mut start, mut break_time_minutes) = (first_day, row.start, row.break_time_minutes);
+                while day < last_day {
+                    let midnight = day.succ().and_hms(0, 0, 0);
+                    attributed.push(DateLine { id: row.id, start, end: midnight, break_time_minutes, date: day.naive_local() });
+                    day = day.succ();
+                    start = midnight;
+                    break_time_minutes = 0;
+                }
+                attributed.push(DateLine { id: row.id, start, end: row.end, break_time_minutes, date: last_day.naive_local() });
+            }
+        }
+    }
+    attributed
+}
+
+/// The time rows that belong to the days from `start` (inclusive) to `end` (exclusive), see `attribute_to_days`.
+/// Entries longer than `MAX_ENTRY_DAYS` are not found.
+pub fn list_times_for_days(storage: &dyn Storage, attribution: MidnightAttribution, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<Vec<DateLine>, Error> {
+    let margin = Duration::days(MAX_ENTRY_DAYS);
+    let rows = storage.list_times(&(*start - margin), &(*end + margin))?;
+    let (first_day, end_day) = (start.naive_local().date(), end.naive_local().date());
+    Ok(attribute_to_days(rows, attribution).into_iter().filter(|row| row.date >= first_day && row.date < end_day).collect())
+}
+
+/// The stored time rows, unsplit, of which at least a part belongs to the days from `start` (inclusive) to `end`
+/// (exclusive), see `attribute_to_days`. With `Split` an entry spanning midnight at the end of the period is found for
+/// both periods.
+pub fn list_entries_for_days(storage: &dyn Storage, attribution: MidnightAttribution, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<Vec<DateLine>, Error> {
+    let margin = Duration::days(MAX_ENTRY_DAYS);
+    let rows = storage.list_times(&(*start - margin), &(*end + margin))?;
+    let (first_day, end_day) = (start.naive_local().date(), end.naive_local().date());
+    let ids: HashSet<i32> = attribute_to_days(rows.clone(), attribution).into_iter()
+        .filter(|row| row.date >= first_day && row.date < end_day)
+        .map(|row| row.id)
+        .collect();
+    Ok(rows.into_iter().filter(|row| ids.contains(&row.id)).collect())
+}
+
+/// The flex balance in hours at `until` (exclusive), i.e. all flex from the first entry up to but not including `until`.
+/// Reports use the same calculation per day, so the balance always equals the sum of the flex of the days before it.
+pub fn calculate_flex_hours(storage: &dyn Storage, default_schedule: &[i32; 7], attribution: MidnightAttribution, until: &DateTime<Local>) -> Result<f64, Error> {
+    let beginning = Local.timestamp(0, 0);
+    let time_rows = list_times_for_days(storage, attribution, &beginning, until)?;
+    let flex_rows = storage.list_flex(&beginning, until)?;
+    let daily_time_overrides = storage.list_daily_time_overrides()?;
+    let absences = storage.list_absences(&beginning, until)?;
+    let holidays = storage.list_holidays(&beginning, until)?;
+    let minutes = calculate_flex_for_period(time_rows, &flex_rows, &daily_time_overrides, default_schedule, &holidays, &absences,
+                                            &beginning.naive_local().date(), &until.naive_local().date());
+    Ok(minutes as f64 / 60.0)
+}
+
+/// Flex in minutes from `start` (inclusive) to `end` (exclusive): the flex of every day plus the manual flex entries.
+/// The rows must already be limited to the period.
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_flex_for_period(time_rows: Vec<DateLine>, flex_rows: &[FlexLine], daily_time_overrides: &[DailyTimeOverrideLine], default_schedule: &[i32; 7],
+                                 holidays: &[HolidayLine], absences: &[AbsenceLine], start: &NaiveDate, end: &NaiveDate) -> i64 {
+    let flex_from_days: i64 = build_map_by_date(time_rows, absences, daily_time_overrides, default_schedule, holidays, start, end).iter()
+        .map(|(date, rows)| calculate_flex(date, rows, daily_time_overrides, default_schedule, holidays, absences))
+        .sum();
+    let flex_from_entries: i64 = flex_rows.iter().map(|row| row.flex_minutes as i64).sum();
+    flex_from_days + flex_from_entries
+}
+
+/// Groups the time rows by date. Days with absence from `start` (inclusive) to `end` (exclusive) are included even if no time was reported.
+pub fn build_map_by_date(time_rows: Vec<DateLine>, absences: &[AbsenceLine], daily_time_overrides: &[DailyTimeOverrideLine], default_schedule: &[i32; 7], holidays: &[HolidayLine], start: &NaiveDate, end: &NaiveDate) -> BTreeMap<NaiveDate, Vec<DateLine>> {
+    let mut map: BTreeMap<NaiveDate, Vec<DateLine>> = BTreeMap::new();
+    for row in time_rows {
+        map.entry(row.date).or_default().push(row);
+    }
+    for absence in absences {
+        let mut date = absence.start.max(*start);
+        while date <= absence.end && date < *end {
+            if get_minutes_for_date(daily_time_overrides, default_schedule, holidays, &date) > 0 {
+                map.entry(date).or_default();
+            }
+            date = date.succ();
+        }
+    }
+    map
+}
+
+/// Flex in minutes for a single date: worked time and absence minus the expected time of the day.
+pub fn calculate_flex(date: &NaiveDate, rows_for_date: &[DateLine], daily_time_overrides: &[DailyTimeOverrideLine], default_schedule: &[i32; 7], holidays: &[HolidayLine], absences: &[AbsenceLine]) -> i64 {
+    let mut sum_minutes = worked_minutes(rows_for_date);
+    let minutes_for_date = get_minutes_for_date(daily_time_overrides, default_schedule, holidays, date);
+    sum_minutes += get_absence_minutes_for_date(absences, date, minutes_for_date);
+    sum_minutes - minutes_for_date
+}
+
+/// Minutes between the start and the end of the rows, minus the breaks.
+pub fn worked_minutes(rows: &[DateLine]) -> i64 {
+    rows.iter().map(|row| (row.end.timestamp() - row.start.timestamp()) / 60 - row.break_time_minutes as i64).sum()
+}
+
+/// Absence counts as worked time, but never more than the expected time of the day.
+fn get_absence_minutes_for_date(absences: &[AbsenceLine], date: &NaiveDate, minutes_for_date: i64) -> i64 {
+    let mut absence_minutes = 0;
+    for absence in absences.iter().filter(|absence| absence.covers(date)) {
+        match absence.minutes_per_day {
+            None => return minutes_for_date,
+            Some(minutes) => absence_minutes += minutes as i64
+        }
+    }
+    absence_minutes.min(minutes_for_date)
+}
+
+/// The expected minutes of work for a date. Holidays have no work, otherwise the schedule that covers the date is used.
+/// If several schedules cover the date, the one that started last wins. Without a schedule `default_schedule`, which
+/// starts with Monday, is used, see `Config::default_schedule`.
+pub fn get_minutes_for_date(daily_time_overrides: &[DailyTimeOverrideLine], default_schedule: &[i32; 7], holidays: &[HolidayLine], date: &NaiveDate) -> i64 {
+    if holidays.iter().any(|holiday| holiday.date == *date) {
+        return 0;
+    }
+    match daily_time_overrides.iter().filter(|override_line| override_line.covers(date)).max_by_key(|override_line| (override_line.start, override_line.id)) {
+        Some(override_line) => override_line.minutes_for(date.weekday()) as i64,
+        None => default_schedule[date.weekday().num_days_from_monday() as usize] as i64
+    }
+}
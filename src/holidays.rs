@@ -0,0 +1,190 @@
+extern crate chrono;
+
+use chrono::{NaiveDate, Datelike, Duration, Weekday};
+
+pub const COUNTRIES: [&str; 5] = ["se", "no", "dk", "fi", "de"];
+
+enum Rule {
+    /// Same month and day every year.
+    Fixed(u32, u32),
+    /// Number of days relative to Easter Sunday.
+    Easter(i64),
+    /// The first given weekday on or after the month and day.
+    WeekdayFrom(Weekday, u32, u32)
+}
+
+fn rules_for_country(country: &str) -> Option<Vec<(Rule, &'static str)>> {
+    match country {
+        "se" => Some(vec![
+            (Rule::Fixed(1, 1), "New Year's Day"),
+            (Rule::Fixed(1, 6), "Epiphany"),
+            (Rule::Easter(-2), "Good Friday"),
+            (Rule::Easter(0), "Easter Sunday"),
+            (Rule::Easter(1), "Easter Monday"),
+            (Rule::Fixed(5, 1), "May Day"),
+            (Rule::Easter(39), "Ascension Day"),
+            (Rule::Easter(49), "Whit Sunday"),
+            (Rule::Fixed(6, 6), "National Day of Sweden"),
+            (Rule::WeekdayFrom(Weekday::Fri, 6, 19), "Midsummer Eve"),
+            (Rule::WeekdayFrom(Weekday::Sat, 6, 20), "Midsummer Day"),
+            (Rule::WeekdayFrom(Weekday::Sat, 10, 31), "All Saints' Day"),
+            (Rule::Fixed(12, 24), "Christmas Eve"),
+            (Rule::Fixed(12, 25), "Christmas Day"),
+            (Rule::Fixed(12, 26), "Boxing Day"),
+            (Rule::Fixed(12, 31), "New Year's Eve")
+        ]),
+        "no" => Some(vec![
+            (Rule::Fixed(1, 1), "New Year's Day"),
+            (Rule::Easter(-3), "Maundy Thursday"),
+            (Rule::Easter(-2), "Good Friday"),
+            (Rule::Easter(0), "Easter Sunday"),
+            (Rule::Easter(1), "Easter Monday"),
+            (Rule::Fixed(5, 1), "Labour Day"),
+            (Rule::Fixed(5, 17), "Constitution Day"),
+            (Rule::Easter(39), "Ascension Day"),
+            (Rule::Easter(49), "Whit Sunday"),
+            (Rule::Easter(50), "Whit Monday"),
+            (Rule::Fixed(12, 25), "Christmas Day"),
+            (Rule::Fixed(12, 26), "Boxing Day")
+        ]),
+        "dk" => Some(vec![
+            (Rule::Fixed(1, 1), "New Year's Day"),
+            (Rule::Easter(-3), "Maundy Thursday"),
+            (Rule::Easter(-2), "Good Friday"),
+            (Rule::Easter(0), "Easter Sunday"),
+            (Rule::Easter(1), "Easter Monday"),
+            (Rule::Easter(39), "Ascension Day"),
+            (Rule::Easter(49), "Whit Sunday"),
+            (Rule::Easter(50), "Whit Monday"),
+            (Rule::Fixed(12, 24), "Christmas Eve"),
+            (Rule::Fixed(12, 25), "Christmas Day"),
+            (Rule::Fixed(12, 26), "Boxing Day")
+        ]),
+        "fi" => Some(vec![
+            (Rule::Fixed(1, 1), "New Year's Day"),
+            (Rule::Fixed(1, 6), "Epiphany"),
+            (Rule::Easter(-2), "Good Friday"),
+            (Rule::Easter(0), "Easter Sunday"),
+            (Rule::Easter(1), "Easter Monday"),
+            (Rule::Fixed(5, 1), "May Day"),
+            (Rule::Easter(39), "Ascension Day"),
+            (Rule::Easter(49), "Whit Sunday"),
+            (Rule::WeekdayFrom(Weekday::Fri, 6, 19), "Midsummer Eve"),
+            (Rule::WeekdayFrom(Weekday::Sat, 6, 20), "Midsummer Day"),
+            (Rule::WeekdayFrom(Weekday::Sat, 10, 31), "All Saints' Day"),
+            (Rule::Fixed(12, 6), "Independence Day"),
+            (Rule::Fixed(12, 24), "Christmas Eve"),
+            (Rule::Fixed(12, 25), "Christmas Day"),
+            (Rule::Fixed(12, 26), "Boxing Day")
+        ]),
+        "de" => Some(vec![
+            (Rule::Fixed(1, 1), "New Year's Day"),
+            (Rule::Easter(-2), "Good Friday"),
+            (Rule::Easter(1), "Easter Monday"),
+            (Rule::Fixed(5, 1), "Labour Day"),
+            (Rule::Easter(39), "Ascension Day"),
+            (Rule::Easter(50), "Whit Monday"),
+            (Rule::Fixed(10, 3), "German Unity Day"),
+            (Rule::Fixed(12, 25), "Christmas Day"),
+            (Rule::Fixed(12, 26), "Boxing Day")
+        ]),
+        _ => None
+    }
+}
+
+/// The public holidays of a year for one of the built in `COUNTRIES`, sorted by date.
+/// Days that are not official holidays but where (almost) nobody works, such as Midsummer Eve in Sweden, are included.
+/// Fails for a country without rules and for a year outside the supported range of dates.
+pub fn holidays_for_country(country: &str, year: i32) -> Result<Vec<(NaiveDate, String)>, String> {
+    let rules = rules_for_country(country).ok_or_else(|| format!("There are no holiday rules for '{}'.", country))?;
+    let out_of_range = || format!("The year {} is out of range.", year);
+    let easter = easter_sunday(year).ok_or_else(out_of_range)?;
+    let mut holidays = Vec::new();
+    for (rule, name) in rules {
+        let date = match rule {
+            Rule::Fixed(month, day) => NaiveDate::from_ymd_opt(year, month, day),
+            Rule::Easter(offset) => easter.checked_add_signed(Duration::days(offset)),
+            Rule::WeekdayFrom(weekday, month, day) => NaiveDate::from_ymd_opt(year, month, day)
+                .map(|date| date + Duration::days((7 + weekday.num_days_from_monday() as i64 - date.weekday().num_days_from_monday() as i64) % 7))
+        };
+        holidays.push((date.ok_or_else(out_of_range)?, name.to_string()));
+    }
+    holidays.sort_by_key(|(date, _)| *date);
+    Ok(holidays)
+}
+
+/// Easter Sunday in the Gregorian calendar (the anonymous Gregorian algorithm). `None` if the year is out of range.
+pub fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// Reads all events from an iCalendar file. Events spanning several days give one holiday per day.
+/// Only the date part of DTSTART and DTEND is used and DTEND is exclusive, as it is for all-day events.
+pub fn parse_ics(content: &str) -> Result<Vec<(NaiveDate, String)>, String> {
+    let mut holidays = Vec::new();
+    let mut start: Option<NaiveDate> = None;
+    let mut end: Option<NaiveDate> = None;
+    let mut summary: Option<String> = None;
+    for (line_number, line) in unfold_ics_lines(content).iter().enumerate() {
+        let (name, value) = match line.find(':') {
+            Some(index) => (&line[..index], &line[index + 1..]),
+            None => continue
+        };
+        let property = name.split(';').next().unwrap_or("").to_uppercase();
+        match property.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                start = None;
+                end = None;
+                summary = None;
+            },
+            "DTSTART" => start = Some(parse_ics_date(value).ok_or(format!("Invalid DTSTART '{}' on line {}", value, line_number + 1))?),
+            "DTEND" => end = Some(parse_ics_date(value).ok_or(format!("Invalid DTEND '{}' on line {}", value, line_number + 1))?),
+            "SUMMARY" => summary = Some(value.replace("\\,", ",").replace("\\;", ";").replace("\\n", " ")),
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                let first_day = start.ok_or("Found an event without DTSTART".to_string())?;
+                let last_day = end.map(|end| end.pred()).unwrap_or(first_day).max(first_day);
+                let name = summary.take().unwrap_or_else(|| "Holiday".to_string());
+                let mut date = first_day;
+                while date <= last_day {
+                    holidays.push((date, name.clone()));
+                    date = date.succ();
+                }
+            },
+            _ => ()
+        }
+    }
+    Ok(holidays)
+}
+
+fn unfold_ics_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(previous) = lines.last_mut() {
+                previous.push_str(&line[1..]);
+            }
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+fn parse_ics_date(value: &str) -> Option<NaiveDate> {
+    value.get(0..8).and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+}
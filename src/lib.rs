@@ -0,0 +1,35 @@
+//! Time tracking without projects: time lines, flex, absence, holidays and reports stored in SQLite.
+//!
+//! `Tracker` is the typed API over the data. The `no-project-time-tracker` binary is a command line interface on top of it.
+
+extern crate chrono;
+extern crate regex;
+
+pub mod db;
+pub mod parsing_utils;
+pub mod report_generation;
+pub mod holidays;
+pub mod flex;
+pub mod csv_import;
+pub mod dump;
+pub mod error;
+pub mod clock;
+pub mod tracker;
+pub mod config;
+pub mod profiles;
+pub mod period;
+mod tests;
+
+pub use crate::clock::Clock;
+pub use crate::config::Config;
+pub use crate::error::NpttError;
+pub use crate::tracker::Tracker;
+use crate::db::db_manager::{DbConnection, DbInitError};
+use std::path::Path;
+
+/// Opens the DB at `path`, creating or migrating the tables as needed.
+pub fn init<P: AsRef<Path>>(path: P, clock: Clock) -> Result<DbConnection, DbInitError> {
+    let connection = db::db_manager::create_connection(path, clock)?;
+    connection.create_tables()?;
+    Ok(connection)
+}
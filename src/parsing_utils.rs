@@ -4,13 +4,19 @@ extern crate chrono;
 
 pub mod validators {
     use regex::Regex;
+
+    pub const TIME_24H_REGEX: &str = "^([01]?[0-9]|2[0-3]):([0-5][0-9])$";
+    pub const TIME_12H_REGEX: &str = "^(?i)(1[0-2]|0?[1-9])(:([0-5][0-9]))? ?([ap])m$";
+
+    /// Accepts 24 hour times like `17:00` and 12 hour times like `9:30am`, `5pm` or `5:15 PM`.
     pub fn time_validator(to_check: String) -> Result<(), String> {
-        let regex = Regex::new("^(2[0-4]|[01][0-9]):([0-6][0-9])$").expect("Invalid regex");
-        return if regex.is_match(to_check.as_str()) {
+        let regex_24h = Regex::new(TIME_24H_REGEX).expect("Invalid regex");
+        let regex_12h = Regex::new(TIME_12H_REGEX).expect("Invalid regex");
+        if regex_24h.is_match(to_check.as_str()) || regex_12h.is_match(to_check.as_str()) {
             Ok(())
         } else {
-            Err(format!("Specified value '{}' is not a valid time format. It should be in HH:mm.", to_check))
-        };
+            Err(format!("Specified value '{}' is not a valid time format. It should be in HH:mm or h:mm followed by am or pm.", to_check))
+        }
     }
 
     pub fn unsigned_number_validator(to_check: String) -> Result<(), String> {
@@ -31,69 +37,193 @@ pub mod validators {
         };
     }
 
+    pub fn unsigned_decimal_validator(to_check: String) -> Result<(), String> {
+        let regex = Regex::new("^[0-9]+(\\.[0-9]+)?$").expect("Invalid regex");
+        if regex.is_match(to_check.as_str()) {
+            Ok(())
+        } else {
+            Err(format!("Specified value '{}' is not a positive number.", to_check))
+        }
+    }
+
+    pub fn signed_decimal_validator(to_check: String) -> Result<(), String> {
+        let regex = Regex::new("^-?[0-9]+(\\.[0-9]+)?$").expect("Invalid regex");
+        if regex.is_match(to_check.as_str()) {
+            Ok(())
+        } else {
+            Err(format!("Specified value '{}' is not a number.", to_check))
+        }
+    }
+
+    pub const DURATION_REGEX: &str = "^(-)?(?:([0-9]+)|([0-9]+):([0-5][0-9])|([0-9]+(?:\\.[0-9]+)?)h|(?:([0-9]+)h)?(?:([0-9]+)m)?)$";
+
+    /// Accepts minutes like `450` and durations like `7h30m`, `7.5h`, `45m` or `1:15`, optionally negative like `-2h`.
+    pub fn duration_validator(to_check: String) -> Result<(), String> {
+        let regex = Regex::new(DURATION_REGEX).expect("Invalid regex");
+        if !to_check.is_empty() && to_check != "-" && regex.is_match(to_check.as_str()) {
+            Ok(())
+        } else {
+            Err(format!("Specified value '{}' is not a duration. Use minutes like 450 or a duration like 7h30m, 7.5h or 1:15.", to_check))
+        }
+    }
+
+    /// Like `duration_validator`, but negative durations are not allowed.
+    pub fn unsigned_duration_validator(to_check: String) -> Result<(), String> {
+        if to_check.starts_with('-') {
+            return Err(format!("Specified value '{}' cannot be negative.", to_check));
+        }
+        duration_validator(to_check)
+    }
+
+    pub const WEEKDAY_REGEX: &str = "(monday|tuesday|wednesday|thursday|friday|saturday|sunday|mon|tue|wed|thu|fri|sat|sun)";
+
+    /// Accepts the dates understood by `parsers::get_date_from_string`. Whether the date exists is decided when parsing.
     pub fn day_validator(to_check: String) -> Result<(), String> {
-        let regex = Regex::new("^(today|yesterday|[0-9]+d|[0-9]{4}-[0-9]{2}-[0-9]{2})$").expect("Invalid regex");
-        return if regex.is_match(to_check.as_str()) {
+        let regex = Regex::new(format!("^(?i)(today|yesterday|[+-]?[0-9]+d|[0-9]{{4}}-[0-9]{{2}}-[0-9]{{2}}|[0-9]{{1,2}}-[0-9]{{1,2}}|((last|next) +)?{}|(this|last|next) +week|([0-9]{{4}}-)?w[0-9]{{1,2}})$", WEEKDAY_REGEX).as_str()).expect("Invalid regex");
+        if regex.is_match(to_check.trim()) {
             Ok(())
         } else {
-            Err(format!("Specified value '{}' is not any of 'today', 'yesterday', an integer followed by 'd' (e.g. 10d), or a valid date (yyyy-mm-dd zero padded).", to_check))
-        };
+            Err(format!("Specified value '{}' is not any of 'today', 'yesterday', an integer followed by 'd' (e.g. 10d, or +2d for the future), a weekday (e.g. friday or 'last friday'), 'this week', 'last week', a week (e.g. W12 or 2024-W12), a date of this year (mm-dd) or a valid date (yyyy-mm-dd zero padded).", to_check))
+        }
     }
 }
 
 pub mod parsers {
-    use chrono::{DateTime, Local, Date, TimeZone, Duration};
+    use chrono::{DateTime, Local, Date, TimeZone, Duration, Datelike, NaiveDate, Weekday};
+    use crate::clock::Clock;
     use regex::Regex;
+    use crate::db::db_manager::AbsenceCategory;
+    use crate::error::NpttError;
+    use super::validators;
 
-    pub fn parse_time(time: Option<&str>) -> Option<(u32, u32)> {
-        time.map(|s| s.split(":").collect())
-            .map(|splitted: Vec<&str>| (splitted[0].to_string().parse().unwrap(), splitted[1].to_string().parse().unwrap()))
+    pub fn parse_time(time: Option<&str>) -> Result<Option<(u32, u32)>, NpttError> {
+        time.map(|time| force_parse_time(time.to_string())).transpose()
     }
 
-    pub fn force_parse_time(time: String) -> (u32, u32) {
-        parse_time(Some(time.as_str())).unwrap()
+    /// Parses a time of day in 24 hour format like `17:00` or in 12 hour format like `9:30am` or `5pm` to hour and minute.
+    pub fn force_parse_time(time: String) -> Result<(u32, u32), NpttError> {
+        let invalid = || NpttError::Parse(format!("Specified value '{}' is not a valid time of day. It should be in HH:mm or h:mm followed by am or pm.", time));
+        let regex_24h = Regex::new(validators::TIME_24H_REGEX).expect("Invalid regex");
+        let regex_12h = Regex::new(validators::TIME_12H_REGEX).expect("Invalid regex");
+        if let Some(cap) = regex_24h.captures(time.trim()) {
+            Ok((cap[1].parse().map_err(|_| invalid())?, cap[2].parse().map_err(|_| invalid())?))
+        } else if let Some(cap) = regex_12h.captures(time.trim()) {
+            let hour = cap[1].parse::<u32>().map_err(|_| invalid())? % 12;
+            let minute = cap.get(3).map(|minute| minute.as_str().parse::<u32>()).transpose().map_err(|_| invalid())?.unwrap_or(0);
+            let afternoon = cap[4].eq_ignore_ascii_case("p");
+            Ok((if afternoon { hour + 12 } else { hour }, minute))
+        } else {
+            Err(invalid())
+        }
     }
 
-    pub fn force_parse_datetime(time: Option<&str>, date: Option<&str>) -> DateTime<Local> {
-        let date = date.unwrap();
-        let (hour, minute) = parse_time(time).unwrap();
-        return get_date_from_string(date)
-            .and_hms(hour,minute, 0);
+    pub fn force_parse_datetime(time: Option<&str>, date: Option<&str>, clock: &Clock) -> Result<DateTime<Local>, NpttError> {
+        let date = get_date_from_string(date.ok_or_else(missing_value)?, clock)?;
+        let (hour, minute) = force_parse_time(time.ok_or_else(missing_value)?.to_string())?;
+        date.and_hms_opt(hour, minute, 0)
+            .ok_or_else(|| NpttError::Validation(format!("{} {:02}:{:02} does not exist in the local time zone.", date.format("%Y-%m-%d"), hour, minute)))
     }
 
-    pub fn force_parse_date(date: Option<&str>) -> Date<Local> {
-        return get_date_from_string(date.unwrap())
+    pub fn force_parse_date(date: Option<&str>, clock: &Clock) -> Result<Date<Local>, NpttError> {
+        get_date_from_string(date.ok_or_else(missing_value)?, clock)
     }
 
-    pub fn get_date_from_string(date_string: &str) -> Date<Local> {
-        let minus_days_regex = Regex::new("^([0-9]+)d$").expect("invalid regex");
-        let date_regex = Regex::new("^([0-9]{4})-([0-9]{2})-([0-9]{2})$").expect("invalid regex");
-        if date_string == "today" {
-            return Local::now().date();
-        } else if date_string == "yesterday" {
-            return Local::now().date().pred();
-        } else if minus_days_regex.is_match(date_string) {
-            match minus_days_regex.captures(date_string) {
-                Some(cap) => return Local::now().date() - Duration::days(cap.get(1).unwrap().as_str().to_string().parse::<i64>().unwrap()),
-                None => panic!("First matched and then didn't? ({})", date_string)
+    /// Parses a date, where today is decided by the clock:
+    /// - `today` and `yesterday`
+    /// - a number of days ago like `10d`, or in the future like `+2d`
+    /// - a weekday like `friday` or `fri`, the most recent one including today
+    /// - `last friday` and `next friday`, the weekday in the week before or after this one
+    /// - `this week`, `last week` and `next week`, the Monday of the week
+    /// - an ISO week like `W12` in this year or `2024-W12`, the Monday of the week
+    /// - `MM-DD` in this year and `YYYY-MM-DD`
+    pub fn get_date_from_string(date_string: &str, clock: &Clock) -> Result<Date<Local>, NpttError> {
+        validators::day_validator(date_string.to_string()).map_err(NpttError::Parse)?;
+        let date_string_lower = date_string.trim().to_lowercase();
+        let date_string_lower = date_string_lower.as_str();
+        let days_regex = Regex::new("^([+-]?)([0-9]+)d$").expect("invalid regex");
+        let date_regex = Regex::new("^(?:([0-9]{4})-)?([0-9]{1,2})-([0-9]{1,2})$").expect("invalid regex");
+        let weekday_regex = Regex::new(format!("^(?:(last|next) +)?{}$", validators::WEEKDAY_REGEX).as_str()).expect("invalid regex");
+        let week_regex = Regex::new("^(this|last|next) +week$").expect("invalid regex");
+        let iso_week_regex = Regex::new("^(?:([0-9]{4})-)?w([0-9]{1,2})$").expect("invalid regex");
+        let invalid = || NpttError::Parse(format!("Specified value '{}' is not a valid date.", date_string));
+        let today = clock.today();
+        let add_days = |date: Date<Local>, days: i64| date.checked_add_signed(Duration::days(days)).ok_or_else(invalid);
+        let monday = add_days(today, -(today.weekday().num_days_from_monday() as i64))?;
+        let week_offset = |word: Option<&str>| match word {
+            Some("last") => -7,
+            Some("next") => 7,
+            _ => 0
+        };
+        if date_string_lower == "today" {
+            Ok(today)
+        } else if date_string_lower == "yesterday" {
+            add_days(today, -1)
+        } else if let Some(cap) = days_regex.captures(date_string_lower) {
+            let days = cap[2].parse::<u32>().map_err(|_| invalid())? as i64;
+            add_days(today, if &cap[1] == "+" { days } else { -days })
+        } else if let Some(cap) = weekday_regex.captures(date_string_lower) {
+            let weekday = cap[2].parse::<Weekday>().map_err(|_| invalid())?;
+            match cap.get(1) {
+                Some(word) => add_days(monday, week_offset(Some(word.as_str())) + weekday.num_days_from_monday() as i64),
+                None => add_days(today, -((7 + today.weekday().num_days_from_monday() as i64 - weekday.num_days_from_monday() as i64) % 7))
             }
-        } else if date_regex.is_match(date_string) {
-            match date_regex.captures(date_string) {
-                Some(cap) => return Local.ymd(cap.get(1).unwrap().as_str().parse::<i32>().unwrap(),
-                                              cap.get(2).unwrap().as_str().parse::<u32>().unwrap(),
-                                              cap.get(3).unwrap().as_str().parse::<u32>().unwrap()),
-                None => panic!("First matched and then didn't? ({})", date_string)
+        } else if let Some(cap) = week_regex.captures(date_string_lower) {
+            add_days(monday, week_offset(cap.get(1).map(|word| word.as_str())))
+        } else if let Some(cap) = iso_week_regex.captures(date_string_lower) {
+            let year = cap.get(1).map(|year| year.as_str().parse::<i32>()).transpose().map_err(|_| invalid())?.unwrap_or_else(|| today.year());
+            let week = cap[2].parse::<u32>().map_err(|_| invalid())?;
+            let date = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).ok_or_else(invalid)?;
+            Local.from_local_date(&date).single().ok_or_else(invalid)
+        } else if let Some(cap) = date_regex.captures(date_string_lower) {
+            let year = cap.get(1).map(|year| year.as_str().parse::<i32>()).transpose().map_err(|_| invalid())?.unwrap_or_else(|| today.year());
+            match (cap[2].parse::<u32>(), cap[3].parse::<u32>()) {
+                (Ok(month), Ok(day)) => Local.ymd_opt(year, month, day).single().ok_or_else(invalid),
+                _ => Err(invalid())
             }
         } else {
-            return panic!("unexpected date string {}", date_string);
+            Err(invalid())
         }
     }
 
-    pub fn force_parse_integer(break_time_string: Option<&str>) -> i32 {
-        return parse_integer(break_time_string).unwrap_or(0);
+    pub fn force_parse_integer(break_time_string: Option<&str>) -> Result<i32, NpttError> {
+        parse_integer(break_time_string).map(|integer| integer.unwrap_or(0))
+    }
+
+    pub fn parse_integer(break_time_string: Option<&str>) -> Result<Option<i32>, NpttError> {
+        break_time_string.map(|str| str.parse::<i32>().map_err(|_| NpttError::Parse(format!("Specified value '{}' is not an integer.", str)))).transpose()
+    }
+
+    pub fn force_parse_duration(duration_string: Option<&str>) -> Result<i32, NpttError> {
+        parse_duration(duration_string).map(|minutes| minutes.unwrap_or(0))
+    }
+
+    /// Parses a duration in minutes, see `validators::duration_validator`. Fractions of a minute are rounded.
+    pub fn parse_duration(duration_string: Option<&str>) -> Result<Option<i32>, NpttError> {
+        duration_string.map(|str| {
+            let invalid = || NpttError::Parse(format!("Specified value '{}' is not a duration. Use minutes like 450 or a duration like 7h30m, 7.5h or 1:15.", str));
+            validators::duration_validator(str.to_string()).map_err(NpttError::Parse)?;
+            let cap = Regex::new(validators::DURATION_REGEX).expect("Invalid regex").captures(str).ok_or_else(invalid)?;
+            let number = |index: usize| cap.get(index).map(|part| part.as_str().parse::<f64>().map_err(|_| invalid())).transpose().map(|number| number.unwrap_or(0.0));
+            let minutes = number(2)? + number(3)? * 60.0 + number(4)? + number(5)? * 60.0 + number(6)? * 60.0 + number(7)?;
+            let minutes = if cap.get(1).is_some() { -minutes } else { minutes }.round();
+            if minutes.abs() > i32::MAX as f64 {
+                return Err(invalid());
+            }
+            Ok(minutes as i32)
+        }).transpose()
+    }
+
+    pub fn force_parse_decimal(decimal_string: Option<&str>) -> Result<f64, NpttError> {
+        decimal_string.map(|str| str.parse::<f64>().map_err(|_| NpttError::Parse(format!("Specified value '{}' is not a number.", str))))
+            .unwrap_or(Ok(0.0))
+    }
+
+    pub fn force_parse_absence_category(category: Option<&str>) -> Result<AbsenceCategory, NpttError> {
+        let category = category.ok_or_else(missing_value)?;
+        AbsenceCategory::from_name(category).ok_or_else(|| NpttError::Parse(format!("'{}' is not an absence category.", category)))
     }
 
-    pub fn parse_integer(break_time_string: Option<&str>) -> Option<i32> {
-        return break_time_string.map(|str| str.to_string().parse::<i32>().unwrap());
+    fn missing_value() -> NpttError {
+        NpttError::Validation("A required value is missing.".to_string())
     }
 }
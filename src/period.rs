@@ -0,0 +1,107 @@
+use crate::clock::Clock;
+use crate::error::NpttError;
+use chrono::{Datelike, DateTime, Duration, Local, NaiveDate, TimeZone, Weekday};
+use regex::Regex;
+
+/// A period from the start (inclusive) to the end (exclusive), both at midnight.
+pub type Period = (DateTime<Local>, DateTime<Local>);
+
+/// The named periods accepted by `parse`, in addition to `YYYY`, `YYYY-MM`, `YYYY-Www` and `YYYY-Qn`.
+pub const NAMES: [&str; 7] = ["this-week", "last-week", "this-month", "last-month", "this-year", "last-year", "ytd"];
+
+/// The date `months` months after `date`, or before for negative `months`. The day is clamped to the length of the
+/// month, so one month after January 31 is the last day of February.
+pub fn plus_months(date: NaiveDate, months: i32) -> Option<NaiveDate> {
+    let month_index = date.year().checked_mul(12)? + date.month0() as i32 + months;
+    let (year, month) = (month_index.div_euclid(12), month_index.rem_euclid(12) as u32 + 1);
+    let days_in_month = (28..=31).rev().find(|day| NaiveDate::from_ymd_opt(year, month, *day).is_some())?;
+    NaiveDate::from_ymd_opt(year, month, date.day().min(days_in_month))
+}
+
+/// The Monday of the week of `date`.
+pub fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+pub fn start_of_month(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), date.month(), 1)
+}
+
+pub fn start_of_quarter(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), date.month0() / 3 * 3 + 1, 1)
+}
+
+pub fn start_of_year(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), 1, 1)
+}
+
+/// The period from midnight at `start` to midnight at `end`. Fails if midnight does not exist in the local time zone.
+pub fn from_dates(start: NaiveDate, end: NaiveDate) -> Result<Period, NpttError> {
+    let midnight = |date: NaiveDate| Local.from_local_datetime(&date.and_hms(0, 0, 0)).single()
+        .ok_or_else(|| NpttError::Validation(format!("Midnight at {} does not exist in the local time zone.", date)));
+    Ok((midnight(start)?, midnight(end)?))
+}
+
+/// The month of today, the default period of most list commands.
+pub fn current_month(clock: &Clock) -> Result<Period, NpttError> {
+    let start = start_of_month(clock.today().naive_local());
+    from_dates(start, next(start, 1)?)
+}
+
+/// The year of today, the default period of list-holidays.
+pub fn current_year(clock: &Clock) -> Result<Period, NpttError> {
+    let start = start_of_year(clock.today().naive_local());
+    from_dates(start, next(start, 12)?)
+}
+
+fn next(start: NaiveDate, months: i32) -> Result<NaiveDate, NpttError> {
+    plus_months(start, months).ok_or_else(|| NpttError::Validation(format!("There is no date {} months after {}.", months, start)))
+}
+
+/// Parses a named period relative to today, see `NAMES`, a year like `2024`, a month like `2024-03`, an ISO week like
+/// `2024-W12` or a quarter like `2024-Q2`. `ytd` is the year to date, including today.
+pub fn parse(period: &str, clock: &Clock) -> Result<Period, NpttError> {
+    validator(period.to_string()).map_err(NpttError::Parse)?;
+    let invalid = || NpttError::Parse(format!("Specified value '{}' is not a valid period.", period));
+    let today = clock.today().naive_local();
+    let period = period.to_lowercase();
+    let captures = Regex::new("^([0-9]{4})(?:-(?:([0-9]{2})|w([0-9]{2})|q([1-4])))?$").expect("Invalid regex").captures(period.as_str());
+    let (start, end) = match (period.as_str(), captures) {
+        ("this-week", _) => (start_of_week(today), start_of_week(today) + Duration::weeks(1)),
+        ("last-week", _) => (start_of_week(today) - Duration::weeks(1), start_of_week(today)),
+        ("this-month", _) => (start_of_month(today), plus_months(start_of_month(today), 1).ok_or_else(invalid)?),
+        ("last-month", _) => (plus_months(start_of_month(today), -1).ok_or_else(invalid)?, start_of_month(today)),
+        ("this-year", _) => (start_of_year(today), plus_months(start_of_year(today), 12).ok_or_else(invalid)?),
+        ("last-year", _) => (plus_months(start_of_year(today), -12).ok_or_else(invalid)?, start_of_year(today)),
+        ("ytd", _) => (start_of_year(today), today.succ()),
+        (_, Some(cap)) => {
+            let year = cap[1].parse::<i32>().map_err(|_| invalid())?;
+            let number = |index: usize| cap.get(index).map(|number| number.as_str().parse::<u32>().map_err(|_| invalid())).transpose();
+            if let Some(month) = number(2)? {
+                let start = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(invalid)?;
+                (start, plus_months(start, 1).ok_or_else(invalid)?)
+            } else if let Some(week) = number(3)? {
+                let start = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).ok_or_else(invalid)?;
+                (start, start + Duration::weeks(1))
+            } else if let Some(quarter) = number(4)? {
+                let start = NaiveDate::from_ymd_opt(year, (quarter - 1) * 3 + 1, 1).ok_or_else(invalid)?;
+                (start, plus_months(start, 3).ok_or_else(invalid)?)
+            } else {
+                let start = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid)?;
+                (start, plus_months(start, 12).ok_or_else(invalid)?)
+            }
+        },
+        _ => return Err(invalid())
+    };
+    from_dates(start, end)
+}
+
+/// Accepts the periods understood by `parse`. Whether a month or week exists is decided when parsing.
+pub fn validator(to_check: String) -> Result<(), String> {
+    let regex = Regex::new(format!("^(?i)({}|[0-9]{{4}}(-([0-9]{{2}}|w[0-9]{{2}}|q[1-4]))?)$", NAMES.join("|")).as_str()).expect("Invalid regex");
+    if regex.is_match(to_check.as_str()) {
+        Ok(())
+    } else {
+        Err(format!("Specified value '{}' is not a period. Use one of {}, a year (2024), a month (2024-03), a week (2024-W12) or a quarter (2024-Q2).", to_check, NAMES.join(", ")))
+    }
+}
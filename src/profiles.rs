@@ -0,0 +1,141 @@
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::db::db_manager::DbConnection;
+use crate::error::NpttError;
+use regex::Regex;
+use std::fs;
+use std::path::PathBuf;
+
+/// The profile that uses the DB and config file from before there were profiles.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profiles keep separate entries, flex, overrides and config, e.g. for two part-time contracts. The default profile
+/// uses `.nptt-db` in the DB dir, other profiles use `.nptt-profiles/<name>.db` next to it and `<name>.toml` in the
+/// profile config dir. The profile used when --profile is not given is stored in `.nptt-profile`.
+pub struct Profiles {
+    db_dir: PathBuf,
+    default_config: Option<PathBuf>,
+    config_dir: Option<PathBuf>,
+    clock: Clock
+}
+
+impl Profiles {
+    /// `default_config` is the config file of the default profile and `config_dir` holds the config files of the other
+    /// profiles. Profiles without a config file use the default config.
+    pub fn new(db_dir: PathBuf, default_config: Option<PathBuf>, config_dir: Option<PathBuf>, clock: Clock) -> Profiles {
+        Profiles { db_dir, default_config, config_dir, clock }
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn db_path(&self, name: &str) -> PathBuf {
+        if name == DEFAULT_PROFILE {
+            self.db_dir.join(".nptt-db")
+        } else {
+            self.profile_dir().join(format!("{}.db", name))
+        }
+    }
+
+    pub fn config_path(&self, name: &str) -> Option<PathBuf> {
+        if name == DEFAULT_PROFILE {
+            self.default_config.clone()
+        } else {
+            self.config_dir.as_ref().map(|dir| dir.join(format!("{}.toml", name)))
+        }
+    }
+
+    fn profile_dir(&self) -> PathBuf {
+        self.db_dir.join(".nptt-profiles")
+    }
+
+    fn current_profile_path(&self) -> PathBuf {
+        self.db_dir.join(".nptt-profile")
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        name == DEFAULT_PROFILE || self.db_path(name).exists()
+    }
+
+    /// All profiles, the default profile first and the others by name.
+    pub fn list(&self) -> Result<Vec<String>, NpttError> {
+        let mut names = Vec::new();
+        if self.profile_dir().exists() {
+            let entries = fs::read_dir(self.profile_dir()).map_err(NpttError::io(format!("Could not list profiles in '{}'", self.profile_dir().display())))?;
+            for entry in entries {
+                let path = entry.map_err(NpttError::io("Could not list profiles".to_string()))?.path();
+                if path.extension().and_then(|extension| extension.to_str()) == Some("db") {
+                    if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        names.insert(0, DEFAULT_PROFILE.to_string());
+        Ok(names)
+    }
+
+    /// The profile selected with `switch`, or the default profile.
+    pub fn current(&self) -> Result<String, NpttError> {
+        let path = self.current_profile_path();
+        if !path.exists() {
+            return Ok(DEFAULT_PROFILE.to_string());
+        }
+        let name = fs::read_to_string(&path).map_err(NpttError::io(format!("Could not read '{}'", path.display())))?;
+        Ok(name.trim().to_string())
+    }
+
+    pub fn switch(&self, name: &str) -> Result<(), NpttError> {
+        self.check_exists(name)?;
+        let path = self.current_profile_path();
+        fs::write(&path, name).map_err(NpttError::io(format!("Could not write '{}'", path.display())))
+    }
+
+    /// Creates the DB of a new profile.
+    pub fn create(&self, name: &str) -> Result<(), NpttError> {
+        name_validator(name.to_string()).map_err(NpttError::Validation)?;
+        if self.exists(name) {
+            return Err(NpttError::Validation(format!("The profile '{}' already exists.", name)));
+        }
+        fs::create_dir_all(self.profile_dir()).map_err(NpttError::io(format!("Could not create profile directory ({})", self.profile_dir().display())))?;
+        crate::init(self.db_path(name), self.clock)?;
+        Ok(())
+    }
+
+    /// Opens the DB of an existing profile. The DB of the default profile is created if it does not exist.
+    pub fn open(&self, name: &str) -> Result<DbConnection, NpttError> {
+        self.check_exists(name)?;
+        if !self.db_dir.exists() {
+            fs::create_dir_all(&self.db_dir).map_err(NpttError::io(format!("Could not create DB directory ({}). Create the folder with correct permissions or set NPTT_DB_LOCATION to a different location", self.db_dir.display())))?;
+        }
+        Ok(crate::init(self.db_path(name), self.clock)?)
+    }
+
+    /// The config of the profile, or the default config if the profile has no config file.
+    pub fn config(&self, name: &str) -> Result<Config, NpttError> {
+        match self.config_path(name) {
+            Some(path) if name == DEFAULT_PROFILE || path.exists() => Config::read(&path),
+            _ => Ok(Config::default())
+        }
+    }
+
+    fn check_exists(&self, name: &str) -> Result<(), NpttError> {
+        if self.exists(name) {
+            Ok(())
+        } else {
+            Err(NpttError::Validation(format!("There is no profile '{}'. Create it with 'profile create {}'.", name, name)))
+        }
+    }
+}
+
+/// Profile names are used as file names, so they may only contain letters, digits, '-' and '_'.
+pub fn name_validator(name: String) -> Result<(), String> {
+    let regex = Regex::new("^[A-Za-z0-9_-]+$").expect("Invalid regex");
+    if regex.is_match(name.as_str()) {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a valid profile name. Use letters, digits, '-' and '_'.", name))
+    }
+}
@@ -1,51 +1,191 @@
-
-use crate::db::db_manager::{DateLine, FlexLine, DailyTimeOverrideLine};
-use chrono::{ DateTime, Local, Date, NaiveDate, Weekday, Datelike };
+use crate::db::db_manager::{DateLine, FlexLine, DailyTimeOverrideLine, AbsenceLine, AbsenceAllowanceLine, AbsenceCategory, HolidayLine};
+use crate::flex::{build_map_by_date, calculate_flex, calculate_flex_for_period, get_minutes_for_date, worked_minutes};
+use crate::period::{start_of_month, start_of_week, start_of_year};
+use chrono::{ DateTime, Local, NaiveDate, Datelike };
+use serde_json::{json, Value};
 use std::collections::btree_map::{ BTreeMap };
 
-pub fn create_csv_report(time_rows: Vec<DateLine>, flex_rows: Vec<FlexLine>, total_flex_hours: f64, daily_time_overrides: Vec<DailyTimeOverrideLine>) -> Vec<String> {
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    Human,
+    Json
+}
+
+impl OutputFormat {
+    pub const NAMES: [&'static str; 2] = ["human", "json"];
+
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "human" => Some(OutputFormat::Human),
+            "json" => Some(OutputFormat::Json),
+            _ => None
+        }
+    }
+}
+
+/// Whether times of day are shown in 24 hour format, 17:30, or in 12 hour format, 5:30 PM. JSON always uses 24 hours.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeFormat {
+    H24,
+    H12
+}
+
+impl TimeFormat {
+    pub const NAMES: [&'static str; 2] = ["24h", "12h"];
+
+    pub fn from_name(name: &str) -> Option<TimeFormat> {
+        match name {
+            "24h" => Some(TimeFormat::H24),
+            "12h" => Some(TimeFormat::H12),
+            _ => None
+        }
+    }
+
+    /// The strftime format of a time of day.
+    pub fn strftime(self) -> &'static str {
+        match self {
+            TimeFormat::H24 => "%H:%M",
+            TimeFormat::H12 => "%-I:%M %p"
+        }
+    }
+}
+
+/// How `report --group-by` adds up the days.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GroupBy {
+    Week,
+    Month,
+    Year
+}
+
+impl GroupBy {
+    pub const NAMES: [&'static str; 3] = ["week", "month", "year"];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GroupBy::Week => "week",
+            GroupBy::Month => "month",
+            GroupBy::Year => "year"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GroupBy> {
+        match name {
+            "week" => Some(GroupBy::Week),
+            "month" => Some(GroupBy::Month),
+            "year" => Some(GroupBy::Year),
+            _ => None
+        }
+    }
+
+    /// The first day of the group of `date`. Weeks start on Monday.
+    pub fn group_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            GroupBy::Week => start_of_week(date),
+            GroupBy::Month => start_of_month(date),
+            GroupBy::Year => start_of_year(date)
+        }
+    }
+
+    /// The name of the group that starts at `start`: an ISO week like 2024-W12, a month like 2024-03 or a year.
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            GroupBy::Week => start.format("%G-W%V").to_string(),
+            GroupBy::Month => start.format("%Y-%m").to_string(),
+            GroupBy::Year => start.format("%Y").to_string()
+        }
+    }
+}
+
+/// The days of one week, month or year added up. Like the per day flex, only days with time entries or absence count,
+/// and the flex also includes the manual flex entries of the group.
+pub struct GroupSummary {
+    pub label: String,
+    pub start: NaiveDate,
+    pub worked_minutes: i64,
+    pub expected_minutes: i64,
+    pub flex_minutes: i64
+}
+
+/// Everything that is needed to generate a report for the period from `start` (inclusive) to `end` (exclusive).
+/// `absences` and `holidays` must cover the time from the start of the year of the period end, so that the absence balance can be calculated.
+pub struct ReportData {
+    pub time_rows: Vec<DateLine>,
+    pub flex_rows: Vec<FlexLine>,
+    pub daily_time_overrides: Vec<DailyTimeOverrideLine>,
+    /// The expected minutes of work per weekday when no daily time override covers the date, starting with Monday.
+    pub default_schedule: [i32; 7],
+    pub absences: Vec<AbsenceLine>,
+    pub absence_allowances: Vec<AbsenceAllowanceLine>,
+    pub holidays: Vec<HolidayLine>,
+    pub opening_flex_hours: f64,
+    pub closing_flex_hours: f64,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>
+}
+
+/// The totals of one profile in a combined report.
+pub struct ProfileSummary {
+    pub name: String,
+    pub worked_hours: f64,
+    pub flex_for_period_hours: f64,
+    pub closing_flex_hours: f64
+}
+
+impl ProfileSummary {
+    pub fn new(name: &str, data: ReportData) -> ProfileSummary {
+        let worked_hours = worked_minutes(&data.time_rows) as f64 / 60.0;
+        let flex_minutes = calculate_flex_for_period(data.time_rows, &data.flex_rows, &data.daily_time_overrides, &data.default_schedule, &data.holidays,
+                                                     &data.absences, &data.start.naive_local().date(), &data.end.naive_local().date());
+        ProfileSummary { name: name.to_string(), worked_hours, flex_for_period_hours: flex_minutes as f64 / 60.0, closing_flex_hours: data.closing_flex_hours }
+    }
+}
+
+struct AbsenceBalance {
+    category: AbsenceCategory,
+    used_days: f64,
+    allowance_days: Option<f64>
+}
+
+/// `time_format` is the strftime format of the start and end times.
+pub fn create_csv_report(data: ReportData, time_format: &str) -> Vec<String> {
     let mut flex_for_period = 0.0;
     let mut lines = Vec::new();
-    let map = build_map_by_date(time_rows);
+    let balances = calculate_absence_balances(&data);
+    let map = build_map_by_date(data.time_rows, &data.absences, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.start.naive_local().date(), &data.end.naive_local().date());
 
-    lines.push("Date,Start,End,Break,Flex (minutes),,,Flex for period (hours),Flex total (hours)".to_string());
+    lines.push("Date,Start,End,Break,Flex (minutes),,,Flex for period (hours),Flex at start of period (hours),Flex at end of period (hours)".to_string());
     for (date, date_lines) in map {
-        let flex = calculate_flex(&date, &date_lines, &daily_time_overrides);
+        let flex = calculate_flex(&date, &date_lines, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.absences);
         flex_for_period += flex as f64 / 60.0;
-        let first_line = &date_lines[0];
-        lines.push(format!("{},{},{},{},{}", date.format("%Y-%m-%d"), first_line.start.format("%H:%M"), first_line.end.format("%H:%M"), first_line.break_time_minutes, flex));
-        for i in 1 .. date_lines.len() {
-            lines.push(format!(",{},{},{},", date_lines[i].start.format("%H:%M"), date_lines[i].end.format("%H:%M"), date_lines[i].break_time_minutes));
+        match date_lines.first() {
+            Some(first_line) => lines.push(format!("{},{},{},{},{}", date.format("%Y-%m-%d"), first_line.start.format(time_format), first_line.end.format(time_format), first_line.break_time_minutes, flex)),
+            None => lines.push(format!("{},,,,{}", date.format("%Y-%m-%d"), flex))
+        }
+        for date_line in date_lines.iter().skip(1) {
+            lines.push(format!(",{},{},{},", date_line.start.format(time_format), date_line.end.format(time_format), date_line.break_time_minutes));
         }
     }
-    for row in &flex_rows {
+    for row in &data.flex_rows {
         flex_for_period += row.flex_minutes as f64 / 60.0;
     }
-    append_string_line_or_push_new(&mut lines, 1, format!("{:.2},{:.2}", flex_for_period, total_flex_hours));
+    append_string_line_or_push_new(&mut lines, 1, format!("{:.2},{:.2},{:.2}", flex_for_period, data.opening_flex_hours, data.closing_flex_hours));
     append_string_line_or_push_new(&mut lines, 3, "Date for flex,Minutes reported,Comment".to_string());
     let mut i = 4;
-    for row in flex_rows {
-        let date: Date<Local> = DateTime::from(row.date).date();
-        append_string_line_or_push_new(&mut lines, i, format!("{},{},{}", date, row.flex_minutes, row.comment));
+    for row in data.flex_rows {
+        append_string_line_or_push_new(&mut lines, i, format!("{},{},{}", row.date.date(), row.flex_minutes, row.comment));
         i += 1;
     }
-    lines
-}
-
-fn build_map_by_date(time_rows: Vec<DateLine>) -> BTreeMap<NaiveDate, Vec<DateLine>> {
-    let mut map: BTreeMap<NaiveDate, Vec<DateLine>> = BTreeMap::new();
-    for row in time_rows {
-        match map.get_mut(&row.date) {
-            None => {
-                let mut list = Vec::new();
-                let date = row.date;
-                list.push(row);
-                map.insert(date, list);
-            },
-            Some(list) => list.push(row)
-        };
+    if !balances.is_empty() {
+        append_string_line_or_push_new(&mut lines, i + 1, format!("Absence category,Days used {},Days remaining", last_day_of_period(&data.end).year()));
+        i += 2;
+        for balance in balances {
+            append_string_line_or_push_new(&mut lines, i, format!("{},{:.2},{}", balance.category.name(), balance.used_days,
+                                                                  balance.allowance_days.map(|allowance| format!("{:.2}", allowance - balance.used_days)).unwrap_or_default()));
+            i += 1;
+        }
     }
-    map
+    lines
 }
 
 fn append_string_line_or_push_new(rows: &mut Vec<String>, index: usize, to_append: String) {
@@ -59,50 +199,290 @@ fn append_string_line_or_push_new(rows: &mut Vec<String>, index: usize, to_appen
     }
 }
 
-pub fn create_human_friendly_report(time_rows: Vec<DateLine>, flex_rows: Vec<FlexLine>, total_flex_hours: f64, start: DateTime<Local>, end: DateTime<Local>, daily_time_overrides: Vec<DailyTimeOverrideLine>) -> Vec<String> {
+/// `date_format` is the strftime format of the dates of the days and `time_format` the one of the start and end times.
+pub fn create_human_friendly_report(data: ReportData, date_format: &str, time_format: &str) -> Vec<String> {
     let mut flex_for_period = 0.0;
     let mut lines = Vec::new();
-    let map = build_map_by_date(time_rows);
-    lines.push(format!("Time entries from {} to {}.", start, end));
+    let balances = calculate_absence_balances(&data);
+    let map = build_map_by_date(data.time_rows, &data.absences, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.start.naive_local().date(), &data.end.naive_local().date());
+    lines.push(format!("Time entries from {} to {}.", data.start.format(date_format), data.end.format(date_format)));
     for (date, date_line) in map {
-        let flex = calculate_flex(&date, &date_line, &daily_time_overrides);
+        let flex = calculate_flex(&date, &date_line, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.absences);
         flex_for_period += flex as f64 / 60.0;
-        lines.push(format!("Got {} flex minutes from {}:", flex, date.format("%Y-%m-%d")));
+        lines.push(format!("Got {} flex minutes from {}:", flex, date.format(date_format)));
+        for holiday in data.holidays.iter().filter(|holiday| holiday.date == date) {
+            lines.push(format!("Public holiday: {}", holiday.name));
+        }
         for date_line in date_line {
-            lines.push(format!("Worked from {} to {} with a break of {} minutes", date_line.start.format("%H:%M"), date_line.end.format("%H:%M"), date_line.break_time_minutes))
+            lines.push(format!("Worked from {} to {} with a break of {} minutes", date_line.start.format(time_format), date_line.end.format(time_format), date_line.break_time_minutes))
+        }
+        for absence in data.absences.iter().filter(|absence| absence.covers(&date)) {
+            match absence.minutes_per_day {
+                None => lines.push(format!("Absent ({}) the whole day", absence.category.name())),
+                Some(minutes) => lines.push(format!("Absent ({}) for {} minutes", absence.category.name(), minutes))
+            }
         }
     }
     lines.push("Manual flex entries:".to_string());
-    for row in flex_rows {
+    for row in data.flex_rows {
         flex_for_period += row.flex_minutes as f64 / 60.0;
-        let date: Date<Local> = DateTime::from(row.date).date();
-        lines.push(format!("Registered {} minutes of flex at {} with comment: '{}'", row.flex_minutes, date, row.comment))
+        lines.push(format!("Registered {} minutes of flex at {} with comment: '{}'", row.flex_minutes, row.date.format(date_format), row.comment))
     }
-    lines.push(format!("Flex diff for selected period: {:.2} hours. Total flex to spend: {:.2} hours", flex_for_period, total_flex_hours));
+    if !balances.is_empty() {
+        lines.push(format!("Absence in {}:", last_day_of_period(&data.end).year()));
+        for balance in balances {
+            match balance.allowance_days {
+                Some(allowance) => lines.push(format!("{}: {:.2} days used, {:.2} of {:.2} days remaining", balance.category.name(), balance.used_days, allowance - balance.used_days, allowance)),
+                None => lines.push(format!("{}: {:.2} days used", balance.category.name(), balance.used_days))
+            }
+        }
+    }
+    lines.push(format!("Flex diff for selected period: {:.2} hours. Flex at start of period: {:.2} hours. Total flex to spend: {:.2} hours",
+                       flex_for_period, data.opening_flex_hours, data.closing_flex_hours));
     lines
 }
 
-fn calculate_flex(date: &NaiveDate, rows_for_date: &Vec<DateLine>, daily_time_overrides: &Vec<DailyTimeOverrideLine>) -> i64 {
-    let mut sum_minutes = 0;
-    for row in rows_for_date {
-        sum_minutes += (row.end.timestamp() - row.start.timestamp()) / 60 - row.break_time_minutes as i64
+/// The groups of the period that have time entries, absence or manual flex, in order.
+pub fn summarize_by(data: &ReportData, group_by: GroupBy) -> Vec<GroupSummary> {
+    let map = build_map_by_date(data.time_rows.clone(), &data.absences, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.start.naive_local().date(), &data.end.naive_local().date());
+    let mut groups: BTreeMap<NaiveDate, GroupSummary> = BTreeMap::new();
+    for (date, date_lines) in map {
+        let summary = group(&mut groups, group_by, date);
+        summary.worked_minutes += worked_minutes(&date_lines);
+        summary.expected_minutes += get_minutes_for_date(&data.daily_time_overrides, &data.default_schedule, &data.holidays, &date);
+        summary.flex_minutes += calculate_flex(&date, &date_lines, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.absences);
+    }
+    for row in &data.flex_rows {
+        group(&mut groups, group_by, row.date.naive_local().date()).flex_minutes += row.flex_minutes as i64;
     }
-    sum_minutes -= get_minutes_for_date(daily_time_overrides, date);
-    return sum_minutes;
+    groups.into_values().collect()
 }
 
-fn get_minutes_for_date(daily_time_overrides: &Vec<DailyTimeOverrideLine>, date: &NaiveDate) -> i64 {
-    return if date.weekday() == Weekday::Sun || date.weekday() == Weekday::Sat {
-        0
-    } else {
-        for override_line in daily_time_overrides {
-            if date.signed_duration_since(override_line.start).num_days() >= 0 {
-                if override_line.end.is_none() || date.signed_duration_since(override_line.end.unwrap()).num_days() < 0 {
-                    return override_line.minutes_of_work as i64;
-                }
+fn group(groups: &mut BTreeMap<NaiveDate, GroupSummary>, group_by: GroupBy, date: NaiveDate) -> &mut GroupSummary {
+    let start = group_by.group_start(date);
+    groups.entry(start).or_insert_with(|| GroupSummary { label: group_by.label(start), start, worked_minutes: 0, expected_minutes: 0, flex_minutes: 0 })
+}
+
+/// The worked, expected and flex minutes summed over all groups.
+fn total(groups: &[GroupSummary]) -> (i64, i64, i64) {
+    groups.iter().fold((0, 0, 0), |(worked, expected, flex), group| (worked + group.worked_minutes, expected + group.expected_minutes, flex + group.flex_minutes))
+}
+
+/// One line per week, month or year instead of one per day.
+pub fn create_human_friendly_summary(data: ReportData, group_by: GroupBy, date_format: &str) -> Vec<String> {
+    let groups = summarize_by(&data, group_by);
+    let mut lines = vec![format!("Summary per {} from {} to {}.", group_by.name(), data.start.format(date_format), data.end.format(date_format))];
+    for group in &groups {
+        lines.push(format!("{}: {:.2} hours worked, {:.2} hours expected, {:.2} hours flex", group.label,
+                           group.worked_minutes as f64 / 60.0, group.expected_minutes as f64 / 60.0, group.flex_minutes as f64 / 60.0));
+    }
+    let (worked, expected, flex) = total(&groups);
+    lines.push(format!("Total: {:.2} hours worked, {:.2} hours expected, {:.2} hours flex", worked as f64 / 60.0, expected as f64 / 60.0, flex as f64 / 60.0));
+    lines.push(format!("Flex diff for selected period: {:.2} hours. Flex at start of period: {:.2} hours. Total flex to spend: {:.2} hours",
+                       flex as f64 / 60.0, data.opening_flex_hours, data.closing_flex_hours));
+    lines
+}
+
+pub fn create_csv_summary(data: ReportData, group_by: GroupBy) -> Vec<String> {
+    let groups = summarize_by(&data, group_by);
+    let mut lines = vec!["Group,Start,Worked (minutes),Expected (minutes),Flex (minutes)".to_string()];
+    for group in &groups {
+        lines.push(format!("{},{},{},{},{}", group.label, group.start.format("%Y-%m-%d"), group.worked_minutes, group.expected_minutes, group.flex_minutes));
+    }
+    let (worked, expected, flex) = total(&groups);
+    lines.push(format!("Total,,{},{},{}", worked, expected, flex));
+    lines
+}
+
+pub fn create_json_summary(data: ReportData, group_by: GroupBy) -> Vec<String> {
+    let groups = summarize_by(&data, group_by);
+    let (worked, expected, flex) = total(&groups);
+    let report = json!({
+        "start": data.start.format("%Y-%m-%d").to_string(),
+        "end": data.end.format("%Y-%m-%d").to_string(),
+        "group_by": group_by.name(),
+        "groups": groups.iter().map(|group| json!({
+            "label": group.label,
+            "start": group.start.format("%Y-%m-%d").to_string(),
+            "worked_minutes": group.worked_minutes,
+            "expected_minutes": group.expected_minutes,
+            "flex_minutes": group.flex_minutes
+        })).collect::<Vec<Value>>(),
+        "worked_minutes": worked,
+        "expected_minutes": expected,
+        "flex_minutes": flex,
+        "opening_flex_hours": data.opening_flex_hours,
+        "closing_flex_hours": data.closing_flex_hours
+    });
+    vec![to_json_string(&report)]
+}
+
+/// Absence days used per category from the start of the year of the period end until the period end.
+/// A partial day counts as the fraction of the expected time of that day.
+fn calculate_absence_balances(data: &ReportData) -> Vec<AbsenceBalance> {
+    let last_day = last_day_of_period(&data.end);
+    let year_start = NaiveDate::from_ymd(last_day.year(), 1, 1);
+    let mut used_days: BTreeMap<AbsenceCategory, f64> = BTreeMap::new();
+    for absence in &data.absences {
+        let mut date = absence.start.max(year_start);
+        while date <= absence.end && date <= last_day {
+            let minutes_for_date = get_minutes_for_date(&data.daily_time_overrides, &data.default_schedule, &data.holidays, &date);
+            if minutes_for_date > 0 {
+                let used = match absence.minutes_per_day {
+                    None => 1.0,
+                    Some(minutes) => (minutes as i64).min(minutes_for_date) as f64 / minutes_for_date as f64
+                };
+                *used_days.entry(absence.category).or_insert(0.0) += used;
             }
+            date = date.succ();
         }
-        8 * 60
     }
+    for allowance in &data.absence_allowances {
+        used_days.entry(allowance.category).or_insert(0.0);
+    }
+    used_days.into_iter()
+        .map(|(category, used_days)| AbsenceBalance {
+            category,
+            used_days,
+            allowance_days: data.absence_allowances.iter().find(|allowance| allowance.category == category).map(|allowance| allowance.days)
+        })
+        .collect()
 }
 
+fn last_day_of_period(end: &DateTime<Local>) -> NaiveDate {
+    end.date().pred().naive_local()
+}
+
+/// The report as a single JSON document. The schema is documented in the README and must stay backwards compatible.
+pub fn create_json_report(data: ReportData) -> Vec<String> {
+    let balances = calculate_absence_balances(&data);
+    let map = build_map_by_date(data.time_rows, &data.absences, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.start.naive_local().date(), &data.end.naive_local().date());
+    let mut flex_for_period = 0.0;
+    let mut days = Vec::new();
+    for (date, date_lines) in map {
+        let flex = calculate_flex(&date, &date_lines, &data.daily_time_overrides, &data.default_schedule, &data.holidays, &data.absences);
+        flex_for_period += flex as f64 / 60.0;
+        days.push(json!({
+            "date": date.format("%Y-%m-%d").to_string(),
+            "flex_minutes": flex,
+            "expected_minutes": get_minutes_for_date(&data.daily_time_overrides, &data.default_schedule, &data.holidays, &date),
+            "holidays": data.holidays.iter().filter(|holiday| holiday.date == date).map(|holiday| holiday.name.clone()).collect::<Vec<String>>(),
+            "time_entries": date_lines.iter().map(time_line_json).collect::<Vec<Value>>(),
+            "absences": data.absences.iter().filter(|absence| absence.covers(&date))
+                .map(|absence| json!({ "category": absence.category.name(), "minutes_per_day": absence.minutes_per_day }))
+                .collect::<Vec<Value>>()
+        }));
+    }
+    for row in &data.flex_rows {
+        flex_for_period += row.flex_minutes as f64 / 60.0;
+    }
+    let year = last_day_of_period(&data.end).year();
+    let report = json!({
+        "start": data.start.format("%Y-%m-%d").to_string(),
+        "end": data.end.format("%Y-%m-%d").to_string(),
+        "days": days,
+        "flex_entries": data.flex_rows.iter().map(flex_line_json).collect::<Vec<Value>>(),
+        "absence_balances": balances.iter().map(|balance| json!({
+            "category": balance.category.name(),
+            "year": year,
+            "used_days": balance.used_days,
+            "allowance_days": balance.allowance_days,
+            "remaining_days": balance.allowance_days.map(|allowance| allowance - balance.used_days)
+        })).collect::<Vec<Value>>(),
+        "flex_for_period_hours": flex_for_period,
+        "opening_flex_hours": data.opening_flex_hours,
+        "closing_flex_hours": data.closing_flex_hours
+    });
+    vec![to_json_string(&report)]
+}
+
+/// The hours of every profile and the total across profiles for the period from `start` (inclusive) to `end` (exclusive).
+pub fn create_human_friendly_combined_report(start: DateTime<Local>, end: DateTime<Local>, date_format: &str, summaries: &[ProfileSummary]) -> Vec<String> {
+    let mut lines = vec![format!("Combined report from {} to {}.", start.format(date_format), end.format(date_format))];
+    for summary in summaries {
+        lines.push(format!("{}: {:.2} hours worked. Flex diff for selected period: {:.2} hours. Total flex to spend: {:.2} hours",
+                           summary.name, summary.worked_hours, summary.flex_for_period_hours, summary.closing_flex_hours));
+    }
+    lines.push(format!("Total: {:.2} hours worked. Flex diff for selected period: {:.2} hours. Total flex to spend: {:.2} hours",
+                       summaries.iter().map(|summary| summary.worked_hours).sum::<f64>(),
+                       summaries.iter().map(|summary| summary.flex_for_period_hours).sum::<f64>(),
+                       summaries.iter().map(|summary| summary.closing_flex_hours).sum::<f64>()));
+    lines
+}
+
+pub fn create_json_combined_report(start: DateTime<Local>, end: DateTime<Local>, summaries: &[ProfileSummary]) -> Vec<String> {
+    let report = json!({
+        "start": start.format("%Y-%m-%d").to_string(),
+        "end": end.format("%Y-%m-%d").to_string(),
+        "profiles": summaries.iter().map(|summary| json!({
+            "name": summary.name,
+            "worked_hours": summary.worked_hours,
+            "flex_for_period_hours": summary.flex_for_period_hours,
+            "closing_flex_hours": summary.closing_flex_hours
+        })).collect::<Vec<Value>>(),
+        "worked_hours": summaries.iter().map(|summary| summary.worked_hours).sum::<f64>(),
+        "flex_for_period_hours": summaries.iter().map(|summary| summary.flex_for_period_hours).sum::<f64>(),
+        "closing_flex_hours": summaries.iter().map(|summary| summary.closing_flex_hours).sum::<f64>()
+    });
+    vec![to_json_string(&report)]
+}
+
+pub fn time_line_json(line: &DateLine) -> Value {
+    json!({
+        "id": line.id,
+        "date": line.date.format("%Y-%m-%d").to_string(),
+        "start": line.start.to_rfc3339(),
+        "end": line.end.to_rfc3339(),
+        "break_minutes": line.break_time_minutes
+    })
+}
+
+pub fn flex_line_json(line: &FlexLine) -> Value {
+    json!({
+        "id": line.id,
+        "date": line.date.format("%Y-%m-%d").to_string(),
+        "flex_minutes": line.flex_minutes,
+        "comment": line.comment
+    })
+}
+
+/// `minutes_per_day` is `null` for absence of whole days.
+pub fn absence_line_json(line: &AbsenceLine) -> Value {
+    json!({
+        "id": line.id,
+        "category": line.category.name(),
+        "start": line.start.format("%Y-%m-%d").to_string(),
+        "end": line.end.format("%Y-%m-%d").to_string(),
+        "minutes_per_day": line.minutes_per_day,
+        "comment": line.comment
+    })
+}
+
+pub fn holiday_line_json(line: &HolidayLine) -> Value {
+    json!({
+        "date": line.date.format("%Y-%m-%d").to_string(),
+        "name": line.name
+    })
+}
+
+pub fn daily_time_override_json(line: &DailyTimeOverrideLine) -> Value {
+    let minutes = line.minutes_per_weekday;
+    json!({
+        "id": line.id,
+        "start": line.start.format("%Y-%m-%d").to_string(),
+        "end": line.end.map(|end| end.format("%Y-%m-%d").to_string()),
+        "minutes_per_weekday": {
+            "monday": minutes[0],
+            "tuesday": minutes[1],
+            "wednesday": minutes[2],
+            "thursday": minutes[3],
+            "friday": minutes[4],
+            "saturday": minutes[5],
+            "sunday": minutes[6]
+        }
+    })
+}
+
+pub fn to_json_string(value: &Value) -> String {
+    serde_json::to_string_pretty(value).expect("Could not serialize JSON.")
+}
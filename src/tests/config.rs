@@ -0,0 +1,52 @@
+use crate::config::Config;
+use crate::report_generation::{OutputFormat, TimeFormat};
+
+#[test]
+fn test_empty_config_has_defaults() {
+    let config = Config::parse("").unwrap();
+    assert_eq!(config, Config::default());
+    assert_eq!(config.default_schedule(), [480, 480, 480, 480, 480, 0, 0]);
+}
+
+#[test]
+fn test_full_config() {
+    let config = Config::parse(r#"
+default_start = "08:00"
+default_end = "5pm"
+default_break = 45
+daily_minutes = 450
+work_days = ["mon", "tue", "wed", "thu"]
+date_format = "%d.%m.%Y"
+output_format = "json"
+time_format = "12h"
+"#).unwrap();
+    assert_eq!(config.default_start.as_deref(), Some("08:00"));
+    assert_eq!(config.default_end.as_deref(), Some("5pm"));
+    assert_eq!(config.default_break, Some(45));
+    assert_eq!(config.default_schedule(), [450, 450, 450, 450, 0, 0, 0]);
+    assert_eq!(config.minutes_per_weekday(300), [300, 300, 300, 300, 0, 0, 0]);
+    assert_eq!(config.date_format, "%d.%m.%Y");
+    assert_eq!(config.output_format, OutputFormat::Json);
+    assert_eq!(config.time_format, TimeFormat::H12);
+}
+
+#[test]
+fn test_durations_in_config() {
+    let config = Config::parse("default_break = \"0:45\"\ndaily_minutes = \"7h30m\"").unwrap();
+    assert_eq!(config.default_break, Some(45));
+    assert_eq!(config.daily_minutes, 450);
+    assert_eq!(Config::parse("daily_minutes = \"7.5h\"").unwrap().daily_minutes, 450);
+}
+
+#[test]
+fn test_invalid_config() {
+    assert!(Config::parse("default_start = \"8\"").is_err());
+    assert!(Config::parse("default_break = -10").is_err());
+    assert!(Config::parse("daily_minutes = \"8x\"").is_err());
+    assert!(Config::parse("default_break = \"-1h\"").is_err());
+    assert!(Config::parse("work_days = [\"monday\"]").is_err());
+    assert!(Config::parse("date_format = \"%Y-%Q\"").is_err());
+    assert!(Config::parse("output_format = \"xml\"").is_err());
+    assert!(Config::parse("time_format = \"am\"").is_err());
+    assert!(Config::parse("unknown_key = 1").is_err());
+}
@@ -0,0 +1,112 @@
+use crate::csv_import::*;
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::db::memory_storage::MemoryStorage;
+use crate::report_generation::create_csv_report;
+use crate::tracker::Tracker;
+use chrono::{Local, TimeZone};
+
+#[test]
+fn test_report_layout() {
+    let content = "Date,Start,End,Break,Flex (minutes),,,Flex for period (hours),Flex at start of period (hours),Flex at end of period (hours)\n\
+                   2019-11-11,08:00,17:00,60,0,,,0.50,0.00,0.50\n\
+                   ,18:00,19:00,0,\n\
+                   2019-11-12,,,,0,,,Date for flex,Minutes reported,Comment\n\
+                   ,,,,,,,2019-11-12+01:00,30,Worked late, again\n\
+                   ,,,,,,,2019-11-13,-15,\n\
+                   \n\
+                   ,,,,,,,Absence category,Days used 2019,Days remaining\n\
+                   ,,,,,,,vacation,1.00,24.00";
+    let entries = parse_csv(content, &Clock::System).ok().unwrap();
+    assert_eq!(entries.len(), 4);
+    match &entries[1] {
+        ImportEntry::Time { start, end, break_time_minutes } => {
+            assert_eq!(*start, Local.ymd(2019, 11, 11).and_hms(18, 0, 0));
+            assert_eq!(*end, Local.ymd(2019, 11, 11).and_hms(19, 0, 0));
+            assert_eq!(*break_time_minutes, 0);
+        },
+        ImportEntry::Flex { .. } => panic!("Expected a time entry")
+    }
+    match &entries[2] {
+        ImportEntry::Flex { date, minutes, comment } => {
+            assert_eq!(*date, Local.ymd(2019, 11, 12));
+            assert_eq!(*minutes, 30);
+            assert_eq!(comment.as_deref(), Some("Worked late, again"));
+        },
+        ImportEntry::Time { .. } => panic!("Expected a flex entry")
+    }
+    match &entries[3] {
+        ImportEntry::Flex { minutes, comment, .. } => {
+            assert_eq!(*minutes, -15);
+            assert!(comment.is_none());
+        },
+        ImportEntry::Time { .. } => panic!("Expected a flex entry")
+    }
+}
+
+#[test]
+fn test_simple_layout() {
+    let content = "Type,Date,Start,End,Break,Minutes,Comment\n\
+                   time,2019-11-11,08:00,17:00,60,,\n\
+                   flex,2019-11-12,,,,30,Overtime\n";
+    let entries = parse_csv(content, &Clock::System).ok().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].describe(), "time from 2019-11-11 08:00 to 17:00 with breaks of 60 minutes");
+    assert_eq!(entries[1].describe(), "30 minutes of flex at 2019-11-12 with comment 'Overtime'");
+}
+
+#[test]
+fn test_errors_have_line_numbers() {
+    let content = "Type,Date,Start,End,Break,Minutes,Comment\n\
+                   time,2019-11-11,08:00,17:00,60,,\n\
+                   time,2019-11-11,8,17:00,60,,\n\
+                   flex,2019-11-12,,,,thirty,\n\
+                   time,2019-11-13,17:00,17:00,0,,\n\
+                   vacation,2019-11-14,,,,,";
+    let errors = parse_csv(content, &Clock::System).err().unwrap();
+    assert_eq!(errors.len(), 4);
+    assert!(errors[0].starts_with("Line 3: "));
+    assert!(errors[1].starts_with("Line 4: "));
+    assert!(errors[2].starts_with("Line 5: The end 2019-11-13 17:00 must be after the start 2019-11-13 17:00."));
+    assert!(errors[3].starts_with("Line 6: Unknown type 'vacation'"));
+}
+
+#[test]
+fn test_lines_spanning_midnight_round_trip() {
+    for attribution in &["start", "split"] {
+        let storage = MemoryStorage::new(Clock::Fixed(Local.ymd(2019, 11, 20).and_hms(12, 0, 0)));
+        let tracker = Tracker::new(&storage, Config::parse(&format!("midnight_attribution = \"{}\"", attribution)).unwrap());
+        let day = Local.ymd(2019, 11, 14);
+        tracker.add_time(&day.and_hms(22, 0, 0), &day.succ().and_hms(2, 0, 0), 0).unwrap();
+        let data = tracker.report_data(&day.and_hms(0, 0, 0), &day.succ().succ().and_hms(0, 0, 0)).unwrap();
+        let entries = parse_csv(&create_csv_report(data, "%H:%M").join("\n"), &Clock::System).ok().unwrap();
+        let descriptions: Vec<String> = entries.iter().map(ImportEntry::describe).collect();
+        assert_eq!(descriptions, vec!["time from 2019-11-14 22:00 to 02:00 with breaks of 0 minutes"]);
+        let minutes: i64 = entries.iter().map(|entry| match entry {
+            ImportEntry::Time { start, end, .. } => (*end - *start).num_minutes(),
+            ImportEntry::Flex { .. } => panic!("Expected a time entry")
+        }).sum();
+        assert_eq!(minutes, 240);
+    }
+}
+
+#[test]
+fn test_rows_meeting_at_midnight_are_joined() {
+    let content = "Type,Date,Start,End,Break,Minutes,Comment\n\
+                   time,2019-11-14,20:00,00:00,30,,\n\
+                   time,2019-11-15,00:00,02:00,0,,\n\
+                   time,2019-11-15,08:00,00:00,0,,\n\
+                   time,2019-11-16,00:00,09:00,0,,\n";
+    let entries = parse_csv(content, &Clock::System).ok().unwrap();
+    let descriptions: Vec<String> = entries.iter().map(ImportEntry::describe).collect();
+    assert_eq!(descriptions, vec!["time from 2019-11-14 20:00 to 02:00 with breaks of 30 minutes",
+                                  "time from 2019-11-15 08:00 to 00:00 with breaks of 0 minutes",
+                                  "time from 2019-11-16 00:00 to 09:00 with breaks of 0 minutes"]);
+}
+
+#[test]
+fn test_unknown_header() {
+    let errors = parse_csv("Something,Else\n1,2", &Clock::System).err().unwrap();
+    assert!(errors[0].starts_with("Line 1: unknown header."));
+    assert!(parse_csv("", &Clock::System).is_err());
+}
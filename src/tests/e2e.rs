@@ -1,47 +1,29 @@
-use crate::*;
-use std::thread::sleep;
-use std::time::Duration;
-
-// This is required as if we keep closing and reopening the connection between each test
-// the sqlite driver seems to run into some race condition and rows that should have been
-// deleted are not.
-#[test]
-fn test_e2e() {
-    let test_connection = init("test-db").unwrap();
-    test_connection.clear();
-    println!("test_add_and_list_line");
-    test_add_and_list_line(&test_connection);
-    test_connection.clear();
-    println!();
-    println!();
-    println!();
-    println!("test_add_and_list_flex");
-    test_add_and_list_flex(&test_connection);
-    test_connection.clear();
-    println!();
-    println!();
-    println!();
-    println!("test_add_and_list_and_stop_daily_time_override");
-    test_add_and_list_and_stop_daily_time_override(&test_connection);
-    test_connection.clear();
-    println!();
-    println!();
-    println!();
-    println!("test_combination_of_stuff");
-    test_combination_of_stuff(&test_connection);
-    test_connection.clear();
+use crate::app::get_app;
+use crate::commands::{execute_commands, execute_with_profiles};
+use nptt::{Clock, Config, NpttError, Tracker};
+use nptt::db::memory_storage::MemoryStorage;
+use nptt::db::storage::Storage;
+use nptt::profiles::Profiles;
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use std::{env, fs};
+
+/// Every test gets its own storage so that the tests can run in parallel.
+fn memory_storage() -> MemoryStorage {
+    MemoryStorage::new(Clock::Fixed(Local.ymd(2019, 11, 20).and_hms(12, 0, 0)))
 }
 
-fn test_add_and_list_line(connection: &DbConnection) {
-    sleep(Duration::from_secs(1));
+#[test]
+fn test_add_and_list_line() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
     let matches = get_app().get_matches_from(vec!["cli-tt", "add-time", "10:00", "19:00", "2019-11-10", "-b60"]);
-    let message = execute_commands(matches, connection).unwrap().get(0).unwrap().to_string();
+    let message = execute_commands(matches, tracker).unwrap().get(0).unwrap().to_string();
     println!("message 1: {}", message);
     assert_eq!(message.contains("2019-11-10"), true);
     assert_eq!(message.contains("10:00"), true);
     assert_eq!(message.contains("19:00"), true);
     assert_eq!(message.contains("60 minutes"), true);
-    let message2 = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-10", "-e2019-11-11"]), connection).unwrap().get(1).unwrap().to_string();
+    let message2 = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-10", "-e2019-11-11"]), tracker).unwrap().get(1).unwrap().to_string();
     println!("message 2: {}", message2);
     assert_eq!(message2.contains("2019-11-10"), true);
     assert_eq!(message2.contains("10:00"), true);
@@ -49,39 +31,89 @@ fn test_add_and_list_line(connection: &DbConnection) {
     assert_eq!(message2.contains("60 minutes"), true);
 }
 
-fn test_add_and_list_flex(connection: &DbConnection) {
-    sleep(Duration::from_secs(1));
+#[test]
+fn test_edit_line() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "10:00", "19:00", "2019-11-10", "-b60"]), tracker).unwrap();
+    let listed = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-10", "-e2019-11-11"]), tracker).unwrap()[1].to_string();
+    println!("listed: {}", listed);
+    assert!(listed.starts_with("id 1:"));
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "edit-time", "1", "--end", "18:30", "--day", "2019-11-11"]), tracker).unwrap()[0].to_string();
+    println!("message edit: {}", message);
+    assert!(message.contains("2019-11-11 10:00"));
+    assert!(message.contains("2019-11-11 18:30"));
+    assert!(message.contains("60 minutes"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-10", "-e2019-11-11"]), tracker).unwrap();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].contains("2019-11-11 18:30"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-11", "-e2019-11-11"]), tracker).unwrap();
+    assert!(lines.last().unwrap().contains("period: -0.50 hours"));
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "edit-time", "1"]), tracker).is_err());
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "edit-time", "2", "-b30"]), tracker).is_err());
+}
+
+#[test]
+fn test_delete_lines() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "10:00", "19:00", "2019-11-10", "-b60"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "30", "2019-11-10"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2019-11-10", "360"]), tracker).unwrap();
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-time", "1", "--yes"]), tracker).unwrap()[0].to_string();
+    println!("message delete time: {}", message);
+    assert!(message.contains("Deleted time line with id 1"));
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-flex", "1", "-y"]), tracker).unwrap()[0].to_string();
+    println!("message delete flex: {}", message);
+    assert!(message.contains("Deleted flex line with id 1"));
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-daily-time-override", "1", "-y"]), tracker).unwrap()[0].to_string();
+    println!("message delete daily time override: {}", message);
+    assert!(message.contains("Deleted daily time override with id 1"));
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-10", "-e2019-11-11"]), tracker).unwrap().len(), 1);
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-flex", "-s2019-11-10", "-e2019-11-11"]), tracker).unwrap().len(), 1);
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-daily-time-override"]), tracker).unwrap().is_empty());
+    let error = execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-time", "1", "-y"]), tracker).unwrap_err();
+    assert!(error.to_string().contains("no time line with id 1"));
+    assert_eq!(error.exit_code(), 1);
+}
+
+#[test]
+fn test_add_and_list_flex() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
     let matches = get_app().get_matches_from(vec!["cli-tt", "add-flex", "30", "2019-11-10", "-c", "Some text here"]);
-    let message = execute_commands(matches, &connection).unwrap().get(0).unwrap().to_string();
+    let message = execute_commands(matches, tracker).unwrap().get(0).unwrap().to_string();
     println!("message 1: {}", message);
     assert_eq!(message.contains("2019-11-10"), true);
     assert_eq!(message.contains("30"), true);
     assert_eq!(message.contains("Some text here"), true);
-    let message2 = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-flex", "-s2019-11-10", "-e2019-11-11"]), connection).unwrap().get(1).unwrap().to_string();
+    let message2 = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-flex", "-s2019-11-10", "-e2019-11-11"]), tracker).unwrap().get(1).unwrap().to_string();
     println!("message 2: {}", message2);
     assert_eq!(message.contains("2019-11-10"), true);
     assert_eq!(message.contains("30"), true);
     assert_eq!(message.contains("Some text here"), true);
 }
 
-fn test_add_and_list_and_stop_daily_time_override(connection: &DbConnection) {
-    sleep(Duration::from_secs(1));
+#[test]
+fn test_add_and_list_and_stop_daily_time_override() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
     let matches = get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2020-02-14", "360"]);
-    let message = execute_commands(matches, &connection).unwrap().get(0).unwrap().to_string();
+    let message = execute_commands(matches, tracker).unwrap().get(0).unwrap().to_string();
     println!("message added: {}", message);
     assert_eq!(message.contains(" 2020-02-14 "), true);
     assert_eq!(message.contains(" 360 "), true);
-    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-daily-time-override"]), &connection).unwrap().get(0).unwrap().to_string();
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-daily-time-override"]), tracker).unwrap().get(0).unwrap().to_string();
     println!("message list time override: {}", message);
     assert_eq!(message.contains(" 2020-02-14 "), true);
     assert_eq!(message.contains(" 360 "), true);
     assert_eq!(message.contains(" never "), true);
     assert_eq!(message.contains(" 1 "), true);
-    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop-daily-time-override", "1", "2020-02-15"]), &connection).unwrap().get(0).unwrap().to_string();
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop-daily-time-override", "1", "2020-02-15"]), tracker).unwrap().get(0).unwrap().to_string();
     println!("message stop time override: {}", message);
     assert_eq!(message.contains(" 2020-02-15."), true);
     assert_eq!(message.contains(" 1 "), true);
-    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-daily-time-override"]), &connection).unwrap().get(0).unwrap().to_string();
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-daily-time-override"]), tracker).unwrap().get(0).unwrap().to_string();
     println!("message list time override: {}", message);
     assert_eq!(message.contains(" 2020-02-14 "), true);
     assert_eq!(message.contains(" 360 "), true);
@@ -89,35 +121,559 @@ fn test_add_and_list_and_stop_daily_time_override(connection: &DbConnection) {
     assert_eq!(message.contains(" 1 "), true);
 }
 
-fn test_combination_of_stuff(connection: &DbConnection) {
-    sleep(Duration::from_secs(1));
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-11", "-b60"]), connection).unwrap(); // +0
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:30", "17:00", "2019-11-12", "-b60"]), connection).unwrap(); // -30 p
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-13", "-b90"]), connection).unwrap(); // -30 p
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:30", "2019-11-14", "-b60"]), connection).unwrap(); // +30 p
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "120", "2019-11-11"]), connection).unwrap(); // +120
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "120", "2019-11-12"]), connection).unwrap(); // +120 p
-    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-12", "-e2019-11-14"]), connection).unwrap();
+#[test]
+fn test_combination_of_stuff() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-11", "-b60"]), tracker).unwrap(); // +0
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:30", "17:00", "2019-11-12", "-b60"]), tracker).unwrap(); // -30 p
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-13", "-b90"]), tracker).unwrap(); // -30 p
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:30", "2019-11-14", "-b60"]), tracker).unwrap(); // +30 p
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "120", "2019-11-11"]), tracker).unwrap(); // +120
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "120", "2019-11-12"]), tracker).unwrap(); // +120 p
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-12", "-e2019-11-14"]), tracker).unwrap();
     for line in &lines {
         println!("{}", line);
     }
     println!();
     assert_eq!(lines.last().unwrap().contains("period: 1.50 hours"), true);
     assert_eq!(lines.last().unwrap().contains("spend: 3.50"), true);
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2019-11-13", "360"]), connection).unwrap(); // Changes the 13th time to be +90 and 14th to be +150 instead (total change + 4 hours)
-    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-12", "-e2019-11-14"]), connection).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2019-11-13", "360"]), tracker).unwrap(); // Changes the 13th time to be +90 and 14th to be +150 instead (total change + 4 hours)
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-12", "-e2019-11-14"]), tracker).unwrap();
     for line in &lines {
         println!("{}", line);
     }
     println!();
     assert_eq!(lines.last().unwrap().contains("period: 5.50 hours"), true);
     assert_eq!(lines.last().unwrap().contains("spend: 7.50"), true);
-    execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop-daily-time-override", "1", "2019-11-14"]), connection).unwrap(); // Changes the 14 time back to +30 (total time reduced by 2 hours)
-    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-12", "-e2019-11-14"]), connection).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop-daily-time-override", "1", "2019-11-14"]), tracker).unwrap(); // Changes the 14 time back to +30 (total time reduced by 2 hours)
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-12", "-e2019-11-14"]), tracker).unwrap();
     for line in &lines {
         println!("{}", line);
     }
     println!();
     assert_eq!(lines.last().unwrap().contains("period: 3.50 hours"), true);
     assert_eq!(lines.last().unwrap().contains("spend: 5.50"), true);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_flex_balance_for_past_period() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "18:00", "2019-11-11", "-b60"]), tracker).unwrap(); // +60
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-12-02", "-b120"]), tracker).unwrap(); // -60
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "30", "2019-12-03"]), tracker).unwrap(); // +30
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-01", "-e2019-11-30"]), tracker).unwrap();
+    println!("{}", lines.last().unwrap());
+    assert!(lines.last().unwrap().contains("period: 1.00 hours"));
+    assert!(lines.last().unwrap().contains("start of period: 0.00 hours"));
+    assert!(lines.last().unwrap().contains("spend: 1.00 hours"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-12-01", "-e2019-12-31"]), tracker).unwrap();
+    println!("{}", lines.last().unwrap());
+    assert!(lines.last().unwrap().contains("period: -0.50 hours"));
+    assert!(lines.last().unwrap().contains("start of period: 1.00 hours"));
+    assert!(lines.last().unwrap().contains("spend: 0.50 hours"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-12-01", "-e2019-12-31", "-c"]), tracker).unwrap();
+    println!("{}", lines[1]);
+    assert!(lines[1].ends_with(",-0.50,1.00,0.50"));
+}
+
+#[test]
+fn test_absence() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-11", "-b60"]), tracker).unwrap(); // +0
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-absence", "vacation", "2019-11-12", "2019-11-17", "-c", "Skiing"]), tracker).unwrap()[0].to_string(); // +0, 4 days
+    println!("message add absence: {}", message);
+    assert!(message.contains("vacation absence from 2019-11-12 to 2019-11-17 for whole days"));
+    assert!(message.contains("Skiing"));
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-absence", "sick", "2019-11-18", "-m240"]), tracker).unwrap(); // -240, 0.5 days
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-absence", "sick", "2019-11-19", "-m240"]), tracker).unwrap(); // 0.5 days
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "12:00", "2019-11-19"]), tracker).unwrap(); // +0
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "set-absence-allowance", "vacation", "25", "--year", "2019"]), tracker).unwrap();
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-11", "-e2019-11-19"]), tracker).unwrap();
+    for line in &lines {
+        println!("{}", line);
+    }
+    assert!(lines.contains(&"Absent (vacation) the whole day".to_string()));
+    assert!(lines.contains(&"Got -240 flex minutes from 2019-11-18:".to_string()));
+    assert!(lines.contains(&"vacation: 4.00 days used, 21.00 of 25.00 days remaining".to_string()));
+    assert!(lines.contains(&"sick: 1.00 days used".to_string()));
+    assert!(lines.last().unwrap().contains("period: -4.00 hours"));
+    assert!(lines.last().unwrap().contains("spend: -4.00"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-11", "-e2019-11-19", "-c"]), tracker).unwrap();
+    assert!(lines.iter().any(|line| line.starts_with("2019-11-18,,,,-240")));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-absence", "-s2019-11-15", "-e2019-11-18"]), tracker).unwrap();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("id 1: vacation absence"));
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-absence", "2", "-y"]), tracker).unwrap();
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-11", "-e2019-11-19"]), tracker).unwrap();
+    assert!(lines.last().unwrap().contains("period: 0.00 hours"));
+    assert!(lines.last().unwrap().contains("spend: 0.00"));
+}
+
+#[test]
+fn test_holidays() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-holidays", "--country", "se", "--year", "2019"]), tracker).unwrap()[0].to_string();
+    println!("message import holidays: {}", message);
+    assert!(message.contains("Imported 16 of 16 holidays"));
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-holidays", "--country", "se", "--year", "2019"]), tracker).unwrap()[0].to_string();
+    assert!(message.contains("Imported 0 of 16 holidays"));
+    let result = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-holidays", "--country", "se", "--year", "300000"]), tracker);
+    assert!(matches!(result, Err(NpttError::Validation(_))));
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-holiday", "2019-06-20", "Company day"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "12:00", "2019-06-20"]), tracker).unwrap(); // +240
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "12:00", "2019-06-21"]), tracker).unwrap(); // +240
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-06-24", "-b60"]), tracker).unwrap(); // +0
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-06-20", "-e2019-06-24"]), tracker).unwrap();
+    for line in &lines {
+        println!("{}", line);
+    }
+    assert!(lines.contains(&"Public holiday: Midsummer Eve".to_string()));
+    assert!(lines.last().unwrap().contains("period: 8.00 hours"));
+    assert!(lines.last().unwrap().contains("spend: 8.00"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-holidays", "-s2019-06-01", "-e2019-06-30"]), tracker).unwrap();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[3], "2019-06-20 Thursday: Company day");
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-holiday", "2019-06-20"]), tracker).unwrap();
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-holiday", "2019-06-20"]), tracker).is_err());
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-06-20", "-e2019-06-24"]), tracker).unwrap();
+    assert!(lines.last().unwrap().contains("period: 0.00 hours"));
+    assert!(lines.last().unwrap().contains("spend: 0.00"));
+}
+
+#[test]
+fn test_timer() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "status"]), tracker).unwrap()[0].to_string();
+    assert_eq!(message, "No timer is running.");
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop"]), tracker).is_err());
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "start", "--at", "00:01"]), tracker).unwrap()[0].to_string();
+    println!("message start: {}", message);
+    assert!(message.contains("00:01"));
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "start"]), tracker).is_err());
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "status", "-b30"]), tracker).unwrap();
+    println!("status: {:?}", lines);
+    assert!(lines[0].contains("Timer running since"));
+    assert!(lines[1].contains("breaks of 30 minutes"));
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop", "--at", "00:00"]), tracker).is_err());
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop", "--at", "00:02", "-b1"]), tracker).unwrap()[0].to_string();
+    println!("message stop: {}", message);
+    assert!(message.contains("00:01"));
+    assert!(message.contains("00:02"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-stoday", "-etoday"]), tracker).unwrap();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].contains("with breaks of 1 minutes"));
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "status"]), tracker).unwrap()[0], "No timer is running.");
+    storage.delete_all_data().unwrap();
+    storage.insert_time(&Local.ymd(2019, 11, 8).and_hms(8, 0, 0), &Local.ymd(2019, 11, 8).and_hms(17, 0, 0), 60).unwrap();
+    storage.start_timer(&Local.ymd(2019, 11, 11).and_hms(8, 0, 0)).unwrap();
+    assert_eq!(storage.get_num_time_entries().unwrap(), 2);
+    assert_eq!(storage.get_date_for_last_entry().unwrap(), NaiveDate::from_ymd(2019, 11, 11));
+}
+
+#[test]
+fn test_leave_at() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "leave-at"]), tracker).is_err());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "00:01", "00:31", "today"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "60", "2019-11-11"]), tracker).unwrap();
+    let today = Local.ymd(2019, 11, 20);
+    let minutes_today = 8 * 60;
+    let format_leave_time = |minutes_after_start: i64| {
+        let leave = today.and_hms(0, 1, 0) + Duration::minutes(minutes_after_start);
+        if leave.date() == today { leave.format("%H:%M").to_string() } else { leave.format("%Y-%m-%d %H:%M").to_string() }
+    };
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "leave-at", "-b30"]), tracker).unwrap();
+    println!("leave at: {:?}", lines);
+    assert!(lines[0].contains(format!("{} to get 0 flex for today", format_leave_time(minutes_today + 30)).as_str()));
+    assert!(lines[1].contains(format!("{} to get a total flex balance of 0.00 hours", format_leave_time(minutes_today + 30 - 60)).as_str()));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "leave-at", "--target", "-1.5"]), tracker).unwrap();
+    println!("leave at: {:?}", lines);
+    assert!(lines[1].contains(format!("{} to get a total flex balance of -1.50 hours", format_leave_time(minutes_today - 60 - 90)).as_str()));
+}
+
+#[test]
+fn test_weekday_schedule() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    assert!(get_app().get_matches_from_safe(vec!["cli-tt", "add-daily-time-override", "2019-11-11"]).is_err());
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2019-11-11", "--fri", "240", "--wed", "0", "--sat", "60"]), tracker).unwrap()[0].to_string();
+    println!("message added: {}", message);
+    assert!(message.contains("mon 480, tue 480, wed 0, thu 480, fri 240, sat 60, sun 0 minutes"));
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-11", "-b60"]), tracker).unwrap(); // 0
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "10:00", "12:00", "2019-11-13", "-b0"]), tracker).unwrap(); // +120
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "12:00", "2019-11-15", "-b0"]), tracker).unwrap(); // 0
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "09:00", "10:30", "2019-11-16", "-b0"]), tracker).unwrap(); // +30
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-11", "-e2019-11-18"]), tracker).unwrap();
+    println!("{:?}", lines);
+    assert!(lines.contains(&"Got 120 flex minutes from 2019-11-13:".to_string()));
+    assert!(lines.contains(&"Got 0 flex minutes from 2019-11-15:".to_string()));
+    assert!(lines.contains(&"Got 30 flex minutes from 2019-11-16:".to_string()));
+    assert!(lines.last().unwrap().contains("period: 2.50 hours"));
+    assert!(lines.last().unwrap().contains("spend: 2.50 hours"));
+}
+
+fn execute_json(args: Vec<&str>, tracker: &Tracker) -> serde_json::Value {
+    let lines = execute_commands(get_app().get_matches_from(args), tracker).unwrap();
+    assert_eq!(lines.len(), 1);
+    println!("{}", lines[0]);
+    serde_json::from_str(lines[0].as_str()).unwrap()
+}
+
+#[test]
+fn test_json_format() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "18:00", "2019-11-11", "-b60"]), tracker).unwrap(); // +60
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "30", "2019-11-12", "-c", "Overtime"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2019-11-13", "--fri", "240"]), tracker).unwrap();
+    let times = execute_json(vec!["cli-tt", "--format", "json", "list-time", "-s2019-11-11", "-e2019-11-11"], tracker);
+    assert_eq!(times["start"], "2019-11-11");
+    assert_eq!(times["end"], "2019-11-12");
+    assert_eq!(times["time_entries"][0]["id"], 1);
+    assert_eq!(times["time_entries"][0]["date"], "2019-11-11");
+    assert_eq!(times["time_entries"][0]["break_minutes"], 60);
+    assert!(times["time_entries"][0]["start"].as_str().unwrap().starts_with("2019-11-11T08:00:00"));
+    let flex = execute_json(vec!["cli-tt", "list-flex", "-s2019-11-12", "-e2019-11-12", "--format", "json"], tracker);
+    assert_eq!(flex["flex_entries"][0]["flex_minutes"], 30);
+    assert_eq!(flex["flex_entries"][0]["comment"], "Overtime");
+    let overrides = execute_json(vec!["cli-tt", "list-daily-time-override", "--format=json"], tracker);
+    assert_eq!(overrides["daily_time_overrides"][0]["start"], "2019-11-13");
+    assert!(overrides["daily_time_overrides"][0]["end"].is_null());
+    assert_eq!(overrides["daily_time_overrides"][0]["minutes_per_weekday"]["thursday"], 480);
+    assert_eq!(overrides["daily_time_overrides"][0]["minutes_per_weekday"]["friday"], 240);
+    let report = execute_json(vec!["cli-tt", "--format", "json", "report", "-s2019-11-01", "-e2019-11-30"], tracker);
+    assert_eq!(report["days"].as_array().unwrap().len(), 1);
+    assert_eq!(report["days"][0]["date"], "2019-11-11");
+    assert_eq!(report["days"][0]["flex_minutes"], 60);
+    assert_eq!(report["days"][0]["time_entries"][0]["id"], 1);
+    assert_eq!(report["flex_entries"][0]["flex_minutes"], 30);
+    assert_eq!(report["flex_for_period_hours"], 1.5);
+    assert_eq!(report["opening_flex_hours"], 0.0);
+    assert_eq!(report["closing_flex_hours"], 1.5);
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "--format", "json", "report", "-c"]), tracker).is_err());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-absence", "vacation", "2019-11-14", "2019-11-15", "-c", "Trip"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-absence", "sick", "2019-11-18", "-m", "120"]), tracker).unwrap();
+    let absences = execute_json(vec!["cli-tt", "--format", "json", "list-absence", "-s2019-11-01", "-e2019-11-30"], tracker);
+    assert_eq!(absences["start"], "2019-11-01");
+    assert_eq!(absences["end"], "2019-12-01");
+    assert_eq!(absences["absences"][0]["category"], "vacation");
+    assert_eq!(absences["absences"][0]["start"], "2019-11-14");
+    assert_eq!(absences["absences"][0]["end"], "2019-11-15");
+    assert!(absences["absences"][0]["minutes_per_day"].is_null());
+    assert_eq!(absences["absences"][0]["comment"], "Trip");
+    assert_eq!(absences["absences"][1]["minutes_per_day"], 120);
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-holiday", "2019-12-25", "Christmas Day"]), tracker).unwrap();
+    let holidays = execute_json(vec!["cli-tt", "list-holidays", "--format", "json", "-s2019-12-01", "-e2019-12-31"], tracker);
+    assert_eq!(holidays["end"], "2020-01-01");
+    assert_eq!(holidays["holidays"][0]["date"], "2019-12-25");
+    assert_eq!(holidays["holidays"][0]["name"], "Christmas Day");
+    assert!(get_app().get_matches_from_safe(vec!["cli-tt", "--format", "xml", "list-time"]).is_err());
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-11", "-e2019-11-11"]), tracker).unwrap();
+    assert!(lines[1].starts_with("id 1: "));
+}
+
+#[test]
+fn test_import_csv() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "18:00", "2019-11-11", "-b60"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "19:00", "20:00", "2019-11-11"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "30", "2019-11-12", "-c", "Overtime"]), tracker).unwrap();
+    let report = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-01", "-e2019-11-30", "-c"]), tracker).unwrap();
+    let path = env::temp_dir().join(format!("nptt-import-{}.csv", std::process::id()));
+    fs::write(&path, report.join("\n")).unwrap();
+    storage.delete_all_data().unwrap();
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-csv", path.to_str().unwrap(), "--dry-run"]), tracker).unwrap();
+    println!("{:?}", lines);
+    assert!(lines[0].starts_with("Would import 2 time entries and 1 flex entries"));
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-01", "-e2019-11-30"]), tracker).unwrap().len(), 1);
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-csv", path.to_str().unwrap()]), tracker).unwrap()[0].to_string();
+    assert!(message.starts_with("Imported 2 time entries and 1 flex entries"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-01", "-e2019-11-30", "-c"]), tracker).unwrap();
+    assert_eq!(lines, report);
+
+    fs::write(&path, "Type,Date,Start,End,Break,Minutes,Comment\ntime,2019-12-02,08:00,17:00,60,,\ntime,2019-12-03,08:00,25:00,60,,").unwrap();
+    let error = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-csv", path.to_str().unwrap()]), tracker).unwrap_err();
+    println!("{}", error);
+    assert!(error.to_string().contains("Line 3: "));
+    assert_eq!(error.exit_code(), 2);
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-12-01", "-e2019-12-31"]), tracker).unwrap().len(), 1);
+
+    let split = &Tracker::new(&storage, Config::parse("midnight_attribution = \"split\"").unwrap());
+    storage.delete_all_data().unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "22:00", "02:00", "2019-11-16", "-b30"]), split).unwrap();
+    let report = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-01", "-e2019-11-30", "-c"]), split).unwrap();
+    let export = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "--period", "2019-11"]), split).unwrap();
+    fs::write(&path, report.join("\n")).unwrap();
+    storage.delete_all_data().unwrap();
+    let message = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-csv", path.to_str().unwrap()]), split).unwrap()[0].to_string();
+    assert!(message.starts_with("Imported 1 time entries and 0 flex entries"));
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "--period", "2019-11"]), split).unwrap(), export);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_export_and_import_all() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    let commands = vec![
+        vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-11", "-b60"],
+        vec!["cli-tt", "add-flex", "30", "2019-11-12", "-c", "Overtime"],
+        vec!["cli-tt", "add-flex", "15", "2019-11-13"],
+        vec!["cli-tt", "add-daily-time-override", "2019-11-13", "--fri", "240"],
+        vec!["cli-tt", "stop-daily-time-override", "1", "2019-12-01"],
+        vec!["cli-tt", "add-absence", "vacation", "2019-11-14", "2019-11-15"],
+        vec!["cli-tt", "set-absence-allowance", "vacation", "25", "--year", "2019"],
+        vec!["cli-tt", "add-holiday", "2019-11-01", "All Saints' Day"],
+        vec!["cli-tt", "start", "--at", "08:00"]
+    ];
+    for command in commands {
+        execute_commands(get_app().get_matches_from(command), tracker).unwrap();
+    }
+    let path = env::temp_dir().join(format!("nptt-export-{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let export = execute_commands(get_app().get_matches_from(vec!["cli-tt", "export-all"]), tracker).unwrap()[0].to_string();
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "export-all", "-o", path]), tracker).unwrap();
+    assert_eq!(lines, vec![format!("Exported to {}:", path), "time entries: 1".to_string(), "flex entries: 2".to_string(), "daily time overrides: 1".to_string(),
+                           "absences: 1".to_string(), "absence allowances: 1".to_string(), "holidays: 1".to_string(), "running timers: 1".to_string()]);
+    assert_eq!(fs::read_to_string(path).unwrap(), export);
+
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-all", path]), tracker).unwrap();
+    println!("{:?}", lines);
+    assert!(lines.contains(&"time entries: 0 added, 1 duplicates skipped".to_string()));
+    assert!(lines.contains(&"flex entries: 0 added, 2 duplicates skipped".to_string()));
+    assert!(lines.contains(&"running timers: 0 added, 1 duplicates skipped".to_string()));
+
+    storage.delete_all_data().unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "09:00", "17:00", "2019-11-18", "-b60"]), tracker).unwrap();
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-all", path]), tracker).unwrap();
+    println!("{:?}", lines);
+    assert!(lines.contains(&"daily time overrides: 1 added, 0 duplicates skipped".to_string()));
+    assert!(lines.contains(&"absences: 1 added, 0 duplicates skipped".to_string()));
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-01", "-e2019-11-30"]), tracker).unwrap().len(), 3);
+
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-all", path, "--replace", "-y"]), tracker).unwrap();
+    assert_eq!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "export-all"]), tracker).unwrap()[0], export);
+
+    fs::write(path, export.replace("\"format_version\": 1", "\"format_version\": 99")).unwrap();
+    let error = execute_commands(get_app().get_matches_from(vec!["cli-tt", "import-all", path]), tracker).unwrap_err();
+    assert!(error.to_string().contains("format version 99"));
+    assert_eq!(error.exit_code(), 1);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_relative_dates() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "yesterday", "-b60"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "30", "3d"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "15", "20d"]), tracker).unwrap();
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-19", "-e2019-11-19"]), tracker).unwrap();
+    assert_eq!(lines.len(), 2);
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-flex"]), tracker).unwrap();
+    println!("{:?}", lines);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].contains("30 minutes of flex at 2019-11-17"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-flex", "-s30d", "-etoday"]), tracker).unwrap();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].contains("15 minutes of flex at 2019-10-31"));
+}
+
+#[test]
+fn test_smart_add() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    let error = execute_commands(get_app().get_matches_from(vec!["cli-tt", "smart-add"]), tracker).unwrap_err();
+    assert!(error.to_string().contains("at least one time entry"));
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-14"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-25"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-holiday", "2019-11-18", "Some holiday"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-absence", "sick", "2019-11-19"]), tracker).unwrap();
+    assert_eq!(tracker.dates_to_report().unwrap(), vec![NaiveDate::from_ymd(2019, 11, 15)]);
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "yesterday"]), tracker).unwrap();
+    assert!(tracker.dates_to_report().unwrap().is_empty());
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "smart-add"]), tracker).unwrap().is_empty());
+}
+
+#[test]
+fn test_config_defaults() {
+    let storage = memory_storage();
+    let config = Config::parse("default_break = 30\ndaily_minutes = 420\nwork_days = [\"mon\", \"tue\", \"wed\", \"thu\"]\ndate_format = \"%d.%m.%Y\"\noutput_format = \"json\"").unwrap();
+    let tracker = &Tracker::new(&storage, config);
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "16:00", "2019-11-14"]), tracker).unwrap();
+    assert!(lines[0].contains("from 14.11.2019 08:00 to 14.11.2019 16:00 with breaks of 30 minutes"));
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "16:00", "2019-11-15", "-b0"]), tracker).unwrap();
+    let report = execute_json(vec!["cli-tt", "report", "-s2019-11-14", "-e2019-11-15"], tracker);
+    assert_eq!(report["days"][0]["expected_minutes"], 420);
+    assert_eq!(report["days"][0]["flex_minutes"], 30);
+    assert_eq!(report["days"][1]["expected_minutes"], 0);
+    assert_eq!(report["days"][1]["flex_minutes"], 480);
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-14", "-e2019-11-15", "--format", "human"]), tracker).unwrap();
+    assert_eq!(lines[0], "Time entries from 14.11.2019 to 16.11.2019.");
+    assert!(lines.contains(&"Got 30 flex minutes from 14.11.2019:".to_string()));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "edit-time", "1", "-b45", "--time-format", "12h"]), tracker).unwrap();
+    assert!(lines[0].contains("from 14.11.2019 8:00 AM to 14.11.2019 4:00 PM with breaks of 45 minutes"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "30", "2019-11-14"]), tracker).unwrap();
+    assert!(lines[0].contains("30 minutes at 14.11.2019"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-flex", "--period", "2019-11", "--format", "human"]), tracker).unwrap();
+    assert_eq!(lines[0], "Rows from 01.11.2019 to 01.12.2019:");
+    assert!(lines[1].contains("30 minutes of flex at 14.11.2019"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2019-11-18", "--thu", "360"]), tracker).unwrap();
+    assert!(lines[0].contains("from 18.11.2019 with mon 420, tue 420, wed 420, thu 360, fri 0, sat 0, sun 0 minutes"));
+}
+
+#[test]
+fn test_lines_spanning_midnight() {
+    let storage = memory_storage();
+    let config = Config::parse("midnight_attribution = \"split\"").unwrap();
+    let tracker = &Tracker::new(&storage, config);
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "22:00", "02:00", "2019-11-16", "-b0"]), tracker).unwrap();
+    assert!(lines[0].contains("from 2019-11-16 22:00 to 2019-11-17 02:00"));
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "23:00", "01:00", "2019-11-23", "-e", "2019-11-24", "-b0"]), tracker).unwrap();
+    assert!(execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "09:00", "2019-11-20", "-e", "2019-11-22"]), tracker).is_err());
+    let report = execute_json(vec!["cli-tt", "report", "-s2019-11-16", "-e2019-11-24", "--format", "json"], tracker);
+    let flex = |date: &str| report["days"].as_array().unwrap().iter().find(|day| day["date"] == date).unwrap()["flex_minutes"].clone();
+    assert_eq!(flex("2019-11-16"), 120);
+    assert_eq!(flex("2019-11-17"), 120);
+    assert_eq!(flex("2019-11-23"), 60);
+    assert_eq!(flex("2019-11-24"), 60);
+    let report = execute_json(vec!["cli-tt", "report", "-s2019-11-17", "-e2019-11-17", "--format", "json"], tracker);
+    assert_eq!(report["flex_for_period_hours"], 2.0);
+}
+
+#[test]
+fn test_line_spanning_month_boundary_is_listed() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "22:00", "02:00", "2019-11-30", "-b0"]), tracker).unwrap();
+    let listed = |period: &str| execute_json(vec!["cli-tt", "list-time", "--period", period, "--format", "json"], tracker)["time_entries"].as_array().unwrap().len();
+    assert_eq!(listed("2019-11"), 1);
+    assert_eq!(listed("2019-12"), 0);
+    let split = Tracker::new(&storage, Config::parse("midnight_attribution = \"split\"").unwrap());
+    assert_eq!(execute_json(vec!["cli-tt", "list-time", "--period", "2019-12", "--format", "json"], &split)["time_entries"][0]["id"], 1);
+    let end = Tracker::new(&storage, Config::parse("midnight_attribution = \"end\"").unwrap());
+    assert!(execute_json(vec!["cli-tt", "list-time", "--period", "2019-11", "--format", "json"], &end)["time_entries"].as_array().unwrap().is_empty());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "delete-time", "1", "--yes"]), tracker).unwrap();
+    assert_eq!(listed("2019-11"), 0);
+}
+
+#[test]
+fn test_12_hour_times() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::parse("time_format = \"12h\"").unwrap());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "9:30am", "5pm", "2019-11-14", "-b30"]), tracker).unwrap();
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "list-time", "-s2019-11-14", "-e2019-11-14"]), tracker).unwrap();
+    assert!(lines[1].contains("from 2019-11-14 9:30 AM to 2019-11-14 5:00 PM"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-14", "-e2019-11-14"]), tracker).unwrap();
+    assert!(lines.contains(&"Worked from 9:30 AM to 5:00 PM with a break of 30 minutes".to_string()));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-c", "-s2019-11-14", "-e2019-11-14", "--time-format", "24h"]), tracker).unwrap();
+    assert!(lines[1].starts_with("2019-11-14,09:30,17:00,30,-60"));
+    let report = execute_json(vec!["cli-tt", "report", "-s2019-11-14", "-e2019-11-14", "--format", "json"], tracker);
+    assert!(report["days"][0]["time_entries"][0]["start"].as_str().unwrap().starts_with("2019-11-14T09:30:00"));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "leave-at", "-b0:30", "--start", "8am"]), tracker).unwrap();
+    assert_eq!(lines[0], "Leave at 4:30 PM to get 0 flex for today.");
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "start", "--at", "11am"]), tracker).unwrap();
+    assert_eq!(lines[0], "Started timer at 2019-11-20 11:00 AM.");
+    let error = execute_commands(get_app().get_matches_from(vec!["cli-tt", "start"]), tracker).unwrap_err();
+    assert!(error.to_string().contains("running since 2019-11-20 11:00 AM."));
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "status"]), tracker).unwrap();
+    assert_eq!(lines[0], "Timer running since 2019-11-20 11:00 AM (1 hours and 0 minutes).");
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "stop", "-b0", "--time-format", "24h"]), tracker).unwrap();
+    assert!(lines[0].contains("from 2019-11-20 11:00 to 2019-11-20 12:00"));
+}
+
+#[test]
+fn test_durations() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-daily-time-override", "2019-11-11", "7.5h", "--fri", "4h"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-14", "-b", "1h"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "12:30", "2019-11-15", "-b", "0:15"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "-2h", "2019-11-15"]), tracker).unwrap();
+    let report = execute_json(vec!["cli-tt", "report", "-s2019-11-14", "-e2019-11-15", "--format", "json"], tracker);
+    assert_eq!(report["days"][0]["flex_minutes"], 30);
+    assert_eq!(report["days"][1]["flex_minutes"], 15);
+    assert_eq!(report["flex_entries"][0]["flex_minutes"], -120);
+    assert!(get_app().get_matches_from_safe(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-14", "-b", "-1h"]).is_err());
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "edit-time", "2", "-b", "1h"]), tracker).unwrap();
+    assert!(lines[0].contains("breaks of 60 minutes"), "{:?}", lines);
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "leave-at", "-b", "0:30", "--start", "08:00"]), tracker).unwrap();
+    assert!(lines[0].contains("Leave at 16:00"), "{:?}", lines);
+}
+
+#[test]
+fn test_named_period() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-08", "-b0"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:00", "2019-11-11", "-b0"]), tracker).unwrap();
+    let listed = execute_json(vec!["cli-tt", "list-time", "--period", "last-week", "--format", "json"], tracker);
+    assert_eq!(listed["start"], "2019-11-11");
+    assert_eq!(listed["time_entries"].as_array().unwrap().len(), 1);
+    let report = execute_json(vec!["cli-tt", "report", "--period", "2019-W45", "--format", "json"], tracker);
+    assert_eq!(report["end"], "2019-11-11");
+    assert_eq!(report["days"][0]["date"], "2019-11-08");
+    assert!(get_app().get_matches_from_safe(vec!["cli-tt", "report", "--period", "ytd", "-s", "today"]).is_err());
+}
+
+#[test]
+fn test_report_grouped_by_week_and_month() {
+    let storage = memory_storage();
+    let tracker = &Tracker::new(&storage, Config::default());
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:30", "2019-10-31", "-b30"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "17:30", "2019-11-11", "-b30"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-time", "08:00", "12:00", "2019-11-18", "-b0"]), tracker).unwrap();
+    execute_commands(get_app().get_matches_from(vec!["cli-tt", "add-flex", "1h", "2019-11-19"]), tracker).unwrap();
+    let report = execute_json(vec!["cli-tt", "report", "--period", "2019", "--group-by", "month", "--format", "json"], tracker);
+    assert_eq!(report["groups"].as_array().unwrap().len(), 2);
+    assert_eq!(report["groups"][0]["label"], "2019-10");
+    assert_eq!(report["groups"][1]["worked_minutes"], 780);
+    assert_eq!(report["groups"][1]["expected_minutes"], 960);
+    assert_eq!(report["groups"][1]["flex_minutes"], -120);
+    assert_eq!(report["flex_minutes"], -60);
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-s2019-11-01", "-e2019-11-30", "--group-by", "week"]), tracker).unwrap();
+    assert_eq!(lines[1], "2019-W46: 9.00 hours worked, 8.00 hours expected, 1.00 hours flex");
+    assert_eq!(lines[2], "2019-W47: 4.00 hours worked, 8.00 hours expected, -3.00 hours flex");
+    assert_eq!(lines[3], "Total: 13.00 hours worked, 16.00 hours expected, -2.00 hours flex");
+    let lines = execute_commands(get_app().get_matches_from(vec!["cli-tt", "report", "-c", "-s2019-11-01", "-e2019-11-30", "--group-by", "year"]), tracker).unwrap();
+    assert_eq!(lines, vec!["Group,Start,Worked (minutes),Expected (minutes),Flex (minutes)", "2019,2019-01-01,780,960,-120", "Total,,780,960,-120"]);
+}
+
+#[test]
+fn test_profiles_and_combined_report() {
+    let dir = env::temp_dir().join(format!("nptt-e2e-profiles-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let profiles = Profiles::new(dir.join("db"), None, Some(dir.join("config")), Clock::Fixed(Local.ymd(2019, 11, 20).and_hms(12, 0, 0)));
+    fs::create_dir_all(dir.join("config")).unwrap();
+    fs::write(dir.join("config").join("part-time.toml"), "daily_minutes = 240").unwrap();
+    let run = |args: Vec<&str>| execute_with_profiles(get_app().get_matches_from(args), &profiles);
+    run(vec!["cli-tt", "profile", "create", "part-time"]).unwrap();
+    assert!(run(vec!["cli-tt", "profile", "create", "part-time"]).is_err());
+    run(vec!["cli-tt", "add-time", "08:00", "13:00", "2019-11-18"]).unwrap();
+    run(vec!["cli-tt", "--profile", "part-time", "add-time", "13:00", "17:00", "2019-11-18"]).unwrap();
+    run(vec!["cli-tt", "profile", "switch", "part-time"]).unwrap();
+    assert_eq!(run(vec!["cli-tt", "profile", "list"]).unwrap(), vec!["default", "part-time (in use)"]);
+    run(vec!["cli-tt", "add-time", "13:00", "18:00", "2019-11-19", "-b30"]).unwrap();
+    let lines = run(vec!["cli-tt", "list-time", "-s2019-11-18", "-e2019-11-19"]).unwrap();
+    assert_eq!(lines.len(), 3);
+    let lines = run(vec!["cli-tt", "--profile", "default", "report", "-s2019-11-18", "-e2019-11-18"]).unwrap();
+    assert!(lines.contains(&"Got -180 flex minutes from 2019-11-18:".to_string()));
+    let lines = run(vec!["cli-tt", "report", "--all-profiles", "-s2019-11-18", "-e2019-11-19"]).unwrap();
+    println!("{:?}", lines);
+    assert!(lines[1].starts_with("default: 5.00 hours worked. Flex diff for selected period: -3.00 hours."));
+    assert!(lines[2].starts_with("part-time: 8.50 hours worked. Flex diff for selected period: 0.50 hours."));
+    assert!(lines[3].starts_with("Total: 13.50 hours worked. Flex diff for selected period: -2.50 hours."));
+    let lines = run(vec!["cli-tt", "report", "--all-profiles", "-s2019-11-18", "-e2019-11-19", "--format", "json"]).unwrap();
+    let report: serde_json::Value = serde_json::from_str(lines[0].as_str()).unwrap();
+    assert_eq!(report["profiles"][1]["name"], "part-time");
+    assert_eq!(report["worked_hours"], 13.5);
+    assert!(run(vec!["cli-tt", "--profile", "missing", "list-time"]).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
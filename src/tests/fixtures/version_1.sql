@@ -0,0 +1,10 @@
+CREATE TABLE time (id INTEGER PRIMARY KEY,start INTEGER NOT NULL,end INTEGER NOT NULL,date TEXT NOT NULL,
+            breakTimeMinutes INTEGER NOT NULL);
+CREATE TABLE flex (id INTEGER PRIMARY KEY,flexMinutes INTEGER NOT NULL,date INTEGER NOT NULL,comment TEXT);
+CREATE TABLE version (version INTEGER NOT NULL);
+CREATE TABLE dailyTime (id INTEGER PRIMARY KEY,startDate TEXT NOT NULL,endDate TEXT,minutesOfWork INTEGER NOT NULL);
+INSERT INTO version(version) VALUES(1);
+INSERT INTO time(date, start, end, breakTimeMinutes) VALUES('2019-11-11', strftime('%s', '2019-11-11 08:00:00', 'utc'), strftime('%s', '2019-11-11 17:00:00', 'utc'), 60);
+INSERT INTO time(date, start, end, breakTimeMinutes) VALUES('2019-11-12', strftime('%s', '2019-11-12 08:30:00', 'utc'), strftime('%s', '2019-11-12 17:00:00', 'utc'), 60);
+INSERT INTO flex(flexMinutes, date, comment) VALUES(120, strftime('%s', '2019-11-11 00:00:00', 'utc'), 'Initial flex');
+INSERT INTO dailyTime(startDate, endDate, minutesOfWork) VALUES('2019-11-12', NULL, 360);
@@ -0,0 +1,164 @@
+use crate::db::db_manager::*;
+use crate::db::storage::Storage;
+use crate::flex::*;
+use crate::clock::Clock;
+use chrono::{Date, Datelike, Duration, Local, NaiveDate, TimeZone};
+use proptest::prelude::*;
+
+const DAYS: u32 = 42;
+
+#[derive(Debug)]
+struct FlexInput {
+    time_rows: Vec<(u32, i64, i64, i32)>,
+    flex_rows: Vec<(u32, i32)>,
+    overrides: Vec<(u32, Option<u32>, [i32; 7])>,
+    default_schedule: [i32; 7],
+    holidays: Vec<u32>,
+    absences: Vec<(u32, u32, Option<i32>)>,
+    attribution: MidnightAttribution
+}
+
+fn day(offset: u32) -> Date<Local> {
+    Local.ymd(2019, 10, 28) + Duration::days(offset as i64)
+}
+
+fn flex_input() -> impl Strategy<Value = FlexInput> {
+    (
+        prop::collection::vec((0..DAYS, 1..1440i64, 1..600i64, 0..90i32), 0..15),
+        prop::collection::vec((0..DAYS, -300..300i32), 0..4),
+        prop::collection::vec((0..DAYS, prop::option::of(1..30u32), prop::array::uniform7(0..600i32)), 0..3),
+        prop::array::uniform7(0..600i32),
+        prop::collection::vec(0..DAYS, 0..4),
+        prop::collection::vec((0..DAYS, 0..7u32, prop::option::of(0..600i32)), 0..3),
+        prop::sample::select(vec![MidnightAttribution::Start, MidnightAttribution::End, MidnightAttribution::Split])
+    ).prop_map(|(time_rows, flex_rows, overrides, default_schedule, holidays, absences, attribution)| FlexInput { time_rows, flex_rows, overrides, default_schedule, holidays, absences, attribution })
+}
+
+fn create_db(input: &FlexInput) -> DbConnection {
+    let connection = create_connection(":memory:", Clock::System).unwrap();
+    connection.create_tables().unwrap();
+    for (offset, start_minute, minutes, break_time) in &input.time_rows {
+        let start = day(*offset).and_hms(0, 0, 0) + Duration::minutes(*start_minute);
+        connection.insert_time(&start, &(start + Duration::minutes(*minutes)), *break_time).unwrap();
+    }
+    for (offset, minutes) in &input.flex_rows {
+        connection.add_flex(*minutes, &day(*offset), None).unwrap();
+    }
+    for (offset, length, minutes_per_weekday) in &input.overrides {
+        connection.add_daily_time_override(&day(*offset), length.map(|length| day(offset + length)).as_ref(), minutes_per_weekday).unwrap();
+    }
+    let holidays: Vec<(NaiveDate, String)> = input.holidays.iter().map(|offset| (day(*offset).naive_local(), "Holiday".to_string())).collect();
+    connection.add_holidays(&holidays).unwrap();
+    for (offset, length, minutes_per_day) in &input.absences {
+        connection.add_absence(AbsenceCategory::Other, &day(*offset), &day(offset + length), *minutes_per_day, None).unwrap();
+    }
+    connection
+}
+
+fn flex_minutes_for_period(connection: &DbConnection, input: &FlexInput, start: Date<Local>, end: Date<Local>) -> i64 {
+    let (start, end) = (start.and_hms(0, 0, 0), end.and_hms(0, 0, 0));
+    calculate_flex_for_period(list_times_for_days(connection, input.attribution, &start, &end).unwrap(),
+                              &connection.list_flex(&start, &end).unwrap(),
+                              &connection.list_daily_time_overrides().unwrap(),
+                              &input.default_schedule,
+                              &connection.list_holidays(&start, &end).unwrap(),
+                              &connection.list_absences(&start, &end).unwrap(),
+                              &start.naive_local().date(), &end.naive_local().date())
+}
+
+fn flex_minutes_until(connection: &DbConnection, input: &FlexInput, until: Date<Local>) -> i64 {
+    (calculate_flex_hours(connection, &input.default_schedule, input.attribution, &until.and_hms(0, 0, 0)).unwrap() * 60.0).round() as i64
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_total_is_sum_of_per_day_flex(input in flex_input()) {
+        let connection = create_db(&input);
+        let start = day(0).and_hms(0, 0, 0);
+        let end = day(DAYS).and_hms(0, 0, 0);
+        let overrides = connection.list_daily_time_overrides().unwrap();
+        let holidays = connection.list_holidays(&start, &end).unwrap();
+        let absences = connection.list_absences(&start, &end).unwrap();
+        let map = build_map_by_date(list_times_for_days(&connection, input.attribution, &start, &end).unwrap(), &absences, &overrides, &input.default_schedule, &holidays, &start.naive_local().date(), &end.naive_local().date());
+        let per_day: i64 = map.iter().map(|(date, rows)| calculate_flex(date, rows, &overrides, &input.default_schedule, &holidays, &absences)).sum();
+        let manual: i64 = connection.list_flex(&start, &end).unwrap().iter().map(|row| row.flex_minutes as i64).sum();
+        prop_assert_eq!(flex_minutes_until(&connection, &input, day(DAYS)), per_day + manual);
+    }
+
+    #[test]
+    fn test_opening_balance_and_period_flex_add_up_to_closing_balance(input in flex_input(), split in 0..=DAYS) {
+        let connection = create_db(&input);
+        let opening = flex_minutes_until(&connection, &input, day(split));
+        let period = flex_minutes_for_period(&connection, &input, day(split), day(DAYS));
+        prop_assert_eq!(opening + period, flex_minutes_until(&connection, &input, day(DAYS)));
+    }
+}
+
+#[test]
+fn test_latest_started_override_wins() {
+    let overrides = vec![
+        DailyTimeOverrideLine { id: 1, start: NaiveDate::from_ymd(2019, 11, 1), end: None, minutes_per_weekday: [360, 360, 360, 360, 360, 0, 0] },
+        DailyTimeOverrideLine { id: 2, start: NaiveDate::from_ymd(2019, 11, 11), end: Some(NaiveDate::from_ymd(2019, 11, 13)), minutes_per_weekday: [240, 240, 240, 240, 240, 0, 0] }
+    ];
+    assert_eq!(get_minutes_for_date(&overrides, &[480, 480, 480, 480, 480, 0, 0], &[], &NaiveDate::from_ymd(2019, 11, 8)), 360);
+    assert_eq!(get_minutes_for_date(&overrides, &[480, 480, 480, 480, 480, 0, 0], &[], &NaiveDate::from_ymd(2019, 11, 11)), 240);
+    assert_eq!(get_minutes_for_date(&overrides, &[480, 480, 480, 480, 480, 0, 0], &[], &NaiveDate::from_ymd(2019, 11, 12)), 240);
+    assert_eq!(get_minutes_for_date(&overrides, &[480, 480, 480, 480, 480, 0, 0], &[], &NaiveDate::from_ymd(2019, 11, 13)), 360);
+    assert_eq!(get_minutes_for_date(&overrides, &[480, 480, 480, 480, 480, 0, 0], &[], &NaiveDate::from_ymd(2019, 11, 16)), 0);
+}
+
+#[test]
+fn test_default_schedule_without_override() {
+    let overrides = vec![
+        DailyTimeOverrideLine { id: 1, start: NaiveDate::from_ymd(2019, 11, 11), end: Some(NaiveDate::from_ymd(2019, 11, 18)), minutes_per_weekday: [240, 240, 240, 240, 240, 0, 0] }
+    ];
+    let default_schedule = [450, 450, 450, 450, 0, 300, 0];
+    assert_eq!(get_minutes_for_date(&overrides, &default_schedule, &[], &NaiveDate::from_ymd(2019, 11, 7)), 450);
+    assert_eq!(get_minutes_for_date(&overrides, &default_schedule, &[], &NaiveDate::from_ymd(2019, 11, 8)), 0);
+    assert_eq!(get_minutes_for_date(&overrides, &default_schedule, &[], &NaiveDate::from_ymd(2019, 11, 9)), 300);
+    assert_eq!(get_minutes_for_date(&overrides, &default_schedule, &[], &NaiveDate::from_ymd(2019, 11, 15)), 240);
+    assert_eq!(get_minutes_for_date(&overrides, &default_schedule, &[], &NaiveDate::from_ymd(2019, 11, 18)), 450);
+}
+
+#[test]
+fn test_midnight_attribution() {
+    let start = Local.ymd(2019, 11, 8).and_hms(22, 0, 0);
+    let rows = vec![DateLine { id: 1, date: start.naive_local().date(), start, end: start + Duration::hours(4), break_time_minutes: 30 }];
+    let minutes_by_day = |attribution| -> Vec<(NaiveDate, i64)> {
+        attribute_to_days(rows.clone(), attribution).iter().map(|row| (row.date, worked_minutes(std::slice::from_ref(row)))).collect()
+    };
+    let (friday, saturday) = (NaiveDate::from_ymd(2019, 11, 8), NaiveDate::from_ymd(2019, 11, 9));
+    assert_eq!(minutes_by_day(MidnightAttribution::Start), vec![(friday, 210)]);
+    assert_eq!(minutes_by_day(MidnightAttribution::End), vec![(saturday, 210)]);
+    assert_eq!(minutes_by_day(MidnightAttribution::Split), vec![(friday, 90), (saturday, 120)]);
+}
+
+#[test]
+fn test_hand_computed_week() {
+    let connection = create_connection(":memory:", Clock::System).unwrap();
+    connection.create_tables().unwrap();
+    let date = |day: u32| Local.ymd(2019, 11, day);
+    connection.add_daily_time_override(&date(4), None, &[420, 420, 420, 420, 420, 0, 0]).unwrap();
+    connection.add_daily_time_override(&date(13), Some(&date(15)), &[240, 240, 240, 240, 240, 0, 0]).unwrap();
+    connection.add_holidays(&[(date(12).naive_local(), "Holiday".to_string())]).unwrap();
+    connection.add_absence(AbsenceCategory::Other, &date(15), &date(15), Some(120), None).unwrap();
+    for (day, start, end, break_time) in &[(11, (8, 0), (16, 0), 30), (12, (9, 0), (11, 0), 0), (13, (8, 0), (12, 30), 0), (14, (8, 0), (11, 0), 0), (15, (8, 0), (13, 0), 30), (16, (10, 0), (11, 0), 0)] {
+        connection.insert_time(&date(*day).and_hms(start.0, start.1, 0), &date(*day).and_hms(end.0, end.1, 0), *break_time).unwrap();
+    }
+    connection.add_flex(-45, &date(14), None).unwrap();
+    connection.add_flex(15, &date(17), None).unwrap();
+    let default_schedule = [480, 480, 480, 480, 480, 0, 0];
+    let (start, end) = (date(11).and_hms(0, 0, 0), date(18).and_hms(0, 0, 0));
+    let overrides = connection.list_daily_time_overrides().unwrap();
+    let holidays = connection.list_holidays(&start, &end).unwrap();
+    let absences = connection.list_absences(&start, &end).unwrap();
+    let map = build_map_by_date(list_times_for_days(&connection, MidnightAttribution::Start, &start, &end).unwrap(), &absences, &overrides, &default_schedule, &holidays, &date(11).naive_local(), &date(18).naive_local());
+    let per_day: Vec<(u32, i64)> = map.iter().map(|(date, rows)| (date.day(), calculate_flex(date, rows, &overrides, &default_schedule, &holidays, &absences))).collect();
+    // Monday: 450 worked of 420. Tuesday is a holiday, so all 120 minutes are flex. Wednesday and Thursday: the newer
+    // override of 240 minutes wins. Friday: 270 worked and 120 absent of 420. Saturday has no expected work.
+    assert_eq!(per_day, vec![(11, 30), (12, 120), (13, 30), (14, -60), (15, -30), (16, 60)]);
+    // 150 from the days and -30 from the manual flex entries.
+    assert_eq!(calculate_flex_hours(&connection, &default_schedule, MidnightAttribution::Start, &end).unwrap(), 2.0);
+}
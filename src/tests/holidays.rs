@@ -0,0 +1,62 @@
+use crate::holidays::*;
+use chrono::NaiveDate;
+
+#[test]
+fn test_easter_sunday() {
+    assert_eq!(easter_sunday(2000), Some(NaiveDate::from_ymd(2000, 4, 23)));
+    assert_eq!(easter_sunday(2019), Some(NaiveDate::from_ymd(2019, 4, 21)));
+    assert_eq!(easter_sunday(2024), Some(NaiveDate::from_ymd(2024, 3, 31)));
+    assert_eq!(easter_sunday(2025), Some(NaiveDate::from_ymd(2025, 4, 20)));
+    assert_eq!(easter_sunday(2038), Some(NaiveDate::from_ymd(2038, 4, 25)));
+}
+
+#[test]
+fn test_swedish_holidays() {
+    let holidays = holidays_for_country("se", 2024).unwrap();
+    assert_eq!(holidays.len(), 16);
+    let find = |name: &str| holidays.iter().find(|(_, holiday)| holiday == name).unwrap().0;
+    assert_eq!(find("Good Friday"), NaiveDate::from_ymd(2024, 3, 29));
+    assert_eq!(find("Ascension Day"), NaiveDate::from_ymd(2024, 5, 9));
+    assert_eq!(find("Midsummer Eve"), NaiveDate::from_ymd(2024, 6, 21));
+    assert_eq!(find("All Saints' Day"), NaiveDate::from_ymd(2024, 11, 2));
+    assert!(holidays.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+}
+
+#[test]
+fn test_unknown_country() {
+    assert!(holidays_for_country("xx", 2024).is_err());
+}
+
+#[test]
+fn test_out_of_range_year() {
+    assert!(easter_sunday(300000).is_none());
+    assert_eq!(holidays_for_country("se", 300000), Err("The year 300000 is out of range.".to_string()));
+    assert!(holidays_for_country("de", -300000).is_err());
+}
+
+#[test]
+fn test_parse_ics() {
+    let ics = "BEGIN:VCALENDAR\r\n\
+               VERSION:2.0\r\n\
+               BEGIN:VEVENT\r\n\
+               DTSTART;VALUE=DATE:20241224\r\n\
+               DTEND;VALUE=DATE:20241227\r\n\
+               SUMMARY:Christmas\\, the long\r\n  version\r\n\
+               END:VEVENT\r\n\
+               BEGIN:VEVENT\r\n\
+               DTSTART:20240606T000000Z\r\n\
+               SUMMARY:National day\r\n\
+               END:VEVENT\r\n\
+               END:VCALENDAR\r\n";
+    let holidays = parse_ics(ics).unwrap();
+    assert_eq!(holidays.len(), 4);
+    assert_eq!(holidays[0], (NaiveDate::from_ymd(2024, 12, 24), "Christmas, the long version".to_string()));
+    assert_eq!(holidays[2].0, NaiveDate::from_ymd(2024, 12, 26));
+    assert_eq!(holidays[3], (NaiveDate::from_ymd(2024, 6, 6), "National day".to_string()));
+}
+
+#[test]
+fn test_parse_invalid_ics() {
+    assert!(parse_ics("BEGIN:VEVENT\nDTSTART:tomorrow\nEND:VEVENT\n").is_err());
+    assert!(parse_ics("BEGIN:VEVENT\nSUMMARY:No date\nEND:VEVENT\n").is_err());
+}
@@ -0,0 +1,97 @@
+use crate::db::db_manager::*;
+use crate::db::storage::Storage;
+use crate::clock::Clock;
+use rusqlite::{Connection, Error, NO_PARAMS};
+use chrono::{Local, TimeZone};
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+
+fn fixture_path(name: &str) -> PathBuf {
+    let mut path = env::temp_dir();
+    path.push(format!("nptt-migration-{}-{}", name, process::id()));
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(backup_path(&path, 1));
+    path
+}
+
+fn backup_path(path: &Path, version: i8) -> PathBuf {
+    let mut backup = path.as_os_str().to_os_string();
+    backup.push(format!(".v{}.bak", version));
+    PathBuf::from(backup)
+}
+
+fn create_version_1_db(name: &str) -> PathBuf {
+    let path = fixture_path(name);
+    Connection::open(&path).unwrap().execute_batch(include_str!("fixtures/version_1.sql")).unwrap();
+    path
+}
+
+fn create_test_table(connection: &Connection) -> Result<(), Error> {
+    connection.execute("CREATE TABLE migrationTest (value INTEGER NOT NULL)", NO_PARAMS).map(|_| ())
+}
+
+fn insert_test_value(connection: &Connection) -> Result<(), Error> {
+    connection.execute("INSERT INTO migrationTest(value) SELECT COUNT(*) FROM time", NO_PARAMS).map(|_| ())
+}
+
+fn failing_migration(connection: &Connection) -> Result<(), Error> {
+    connection.execute("CREATE TABLE neverCreated (value INTEGER)", NO_PARAMS)?;
+    connection.execute("INSERT INTO tableThatDoesNotExist(value) VALUES(1)", NO_PARAMS).map(|_| ())
+}
+
+#[test]
+fn test_version_1_fixture_is_migrated_to_latest() {
+    let path = create_version_1_db("latest");
+    let connection = create_connection(&path, Clock::System).unwrap();
+    connection.create_tables().unwrap();
+    assert_eq!(connection.get_version().unwrap() as usize, MIGRATIONS.len() + 1);
+    let times = connection.list_times(&Local.ymd(2019, 11, 1).and_hms(0, 0, 0), &Local.ymd(2019, 12, 1).and_hms(0, 0, 0)).unwrap();
+    assert_eq!(times.len(), 2);
+    assert_eq!(times[0].break_time_minutes, 60);
+    let overrides = connection.list_daily_time_overrides().unwrap();
+    assert_eq!(overrides.len(), 1);
+    assert_eq!(overrides[0].minutes_per_weekday, [360, 360, 360, 360, 360, 0, 0]);
+}
+
+#[test]
+fn test_migrations_are_applied_in_order_after_backup() {
+    let path = create_version_1_db("in-order");
+    let connection = create_connection(&path, Clock::System).unwrap();
+    connection.create_tables_with_migrations(&[create_test_table, insert_test_value]).unwrap();
+    assert_eq!(connection.get_version().unwrap(), 3);
+    let migrated: Connection = Connection::open(&path).unwrap();
+    assert_eq!(migrated.query_row("SELECT value FROM migrationTest", NO_PARAMS, |row| row.get::<usize, i32>(0)).unwrap(), 2);
+    let backup = create_connection(backup_path(&path, 1), Clock::System).unwrap();
+    assert_eq!(backup.get_version().unwrap(), 1);
+}
+
+#[test]
+fn test_failing_migration_is_rolled_back() {
+    let path = create_version_1_db("rollback");
+    let connection = create_connection(&path, Clock::System).unwrap();
+    assert!(connection.create_tables_with_migrations(&[create_test_table, failing_migration]).is_err());
+    assert_eq!(connection.get_version().unwrap(), 2);
+    let migrated: Connection = Connection::open(&path).unwrap();
+    assert!(migrated.query_row("SELECT COUNT(*) FROM neverCreated", NO_PARAMS, |row| row.get::<usize, i32>(0)).is_err());
+    assert!(migrated.query_row("SELECT COUNT(*) FROM migrationTest", NO_PARAMS, |row| row.get::<usize, i32>(0)).is_ok());
+}
+
+#[test]
+fn test_newer_version_is_rejected() {
+    let path = create_version_1_db("newer");
+    Connection::open(&path).unwrap().execute("UPDATE version SET version=5", NO_PARAMS).unwrap();
+    let connection = create_connection(&path, Clock::System).unwrap();
+    match connection.create_tables_with_migrations(&[create_test_table]) {
+        Err(DbInitError::UnknownVersion(5)) => (),
+        _ => panic!("Expected the DB version to be rejected")
+    }
+}
+
+#[test]
+fn test_new_db_is_created_at_latest_version_without_backup() {
+    let path = fixture_path("new");
+    let connection = create_connection(&path, Clock::System).unwrap();
+    connection.create_tables_with_migrations(&[create_test_table, insert_test_value]).unwrap();
+    assert_eq!(connection.get_version().unwrap(), 3);
+    assert!(!backup_path(&path, 1).exists());
+}
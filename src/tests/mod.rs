@@ -1,3 +1,20 @@
 #[cfg(test)]
 pub mod parsing_utils;
-pub mod e2e;
\ No newline at end of file
+#[cfg(test)]
+pub mod migrations;
+#[cfg(test)]
+pub mod holidays;
+#[cfg(test)]
+pub mod flex;
+#[cfg(test)]
+pub mod csv_import;
+#[cfg(test)]
+pub mod tracker;
+#[cfg(test)]
+pub mod storage;
+#[cfg(test)]
+pub mod config;
+#[cfg(test)]
+pub mod profiles;
+#[cfg(test)]
+pub mod period;
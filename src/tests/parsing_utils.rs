@@ -1,10 +1,15 @@
 use crate::parsing_utils::*;
-use chrono::{Local, Duration, TimeZone};
+use crate::clock::Clock;
+use chrono::{Local, TimeZone};
+
+fn clock() -> Clock {
+    Clock::Fixed(Local.ymd(2019, 11, 12).and_hms(10, 30, 0))
+}
 
 #[test]
 fn test_day_today(){
     assert_eq!(validators::day_validator("today".to_string()).is_ok(), true);
-    assert_eq!(parsers::get_date_from_string("today"), Local::now().date());
+    assert_eq!(parsers::get_date_from_string("today", &clock()).unwrap(), Local.ymd(2019, 11, 12));
 }
 
 #[test]
@@ -15,29 +20,63 @@ fn test_invalid_day_todays(){
 #[test]
 fn test_day_yesterday(){
     assert_eq!(validators::day_validator("yesterday".to_string()).is_ok(), true);
-    assert_eq!(parsers::get_date_from_string("yesterday"), Local::now().date().pred());
+    assert_eq!(parsers::get_date_from_string("yesterday", &clock()).unwrap(), Local.ymd(2019, 11, 11));
+    assert_eq!(parsers::get_date_from_string("yesterday", &Clock::Fixed(Local.ymd(2020, 1, 1).and_hms(0, 0, 0))).unwrap(), Local.ymd(2019, 12, 31));
 }
 
 #[test]
 fn test_day_x_days(){
     assert_eq!(validators::day_validator("10d".to_string()).is_ok(), true);
-    assert_eq!(parsers::get_date_from_string("10d"), (Local::now() - Duration::days(10)).date());
+    assert_eq!(parsers::get_date_from_string("10d", &clock()).unwrap(), Local.ymd(2019, 11, 2));
+    assert_eq!(parsers::get_date_from_string("0d", &clock()).unwrap(), Local.ymd(2019, 11, 12));
+    assert_eq!(parsers::get_date_from_string("12d", &clock()).unwrap(), Local.ymd(2019, 10, 31));
+    assert_eq!(parsers::get_date_from_string("366d", &clock()).unwrap(), Local.ymd(2018, 11, 11));
 }
 
 #[test]
 fn test_day_date(){
     assert_eq!(validators::day_validator("2019-11-10".to_string()).is_ok(), true);
-    assert_eq!(parsers::get_date_from_string("2019-11-10"), Local.ymd(2019, 11, 10));
+    assert_eq!(parsers::get_date_from_string("2019-11-10", &clock()).unwrap(), Local.ymd(2019, 11, 10));
+}
+
+#[test]
+fn test_day_relative(){
+    // The clock is at Tuesday 2019-11-12.
+    let date = |date_string: &str| parsers::get_date_from_string(date_string, &clock()).unwrap();
+    assert_eq!(date("+2d"), Local.ymd(2019, 11, 14));
+    assert_eq!(date("-2d"), Local.ymd(2019, 11, 10));
+    assert_eq!(date("tuesday"), Local.ymd(2019, 11, 12));
+    assert_eq!(date("monday"), Local.ymd(2019, 11, 11));
+    assert_eq!(date("Fri"), Local.ymd(2019, 11, 8));
+    assert_eq!(date("last friday"), Local.ymd(2019, 11, 8));
+    assert_eq!(date("last monday"), Local.ymd(2019, 11, 4));
+    assert_eq!(date("next monday"), Local.ymd(2019, 11, 18));
+    assert_eq!(date("this week"), Local.ymd(2019, 11, 11));
+    assert_eq!(date("last week"), Local.ymd(2019, 11, 4));
+    assert_eq!(date("W1"), Local.ymd(2018, 12, 31));
+    assert_eq!(date("2024-W12"), Local.ymd(2024, 3, 18));
+    assert_eq!(date("11-1"), Local.ymd(2019, 11, 1));
+    assert_eq!(date("02-28"), Local.ymd(2019, 2, 28));
+}
+
+#[test]
+fn test_invalid_relative_day_is_an_error(){
+    assert!(validators::day_validator("last".to_string()).is_err());
+    assert!(validators::day_validator("friday week".to_string()).is_err());
+    for invalid in &["02-29", "13-01", "W54", "2019-W53", "+99999999999d"] {
+        assert!(validators::day_validator(invalid.to_string()).is_ok());
+        assert_eq!(parsers::get_date_from_string(invalid, &clock()).unwrap_err().exit_code(), 2, "{} should be invalid", invalid);
+    }
 }
 
 #[test]
 fn test_parse_brake_time(){
     assert_eq!(validators::unsigned_number_validator("59".to_string()).is_ok(), true);
-    assert_eq!(parsers::force_parse_integer(Some("59")), 59);
+    assert_eq!(parsers::force_parse_integer(Some("59")).unwrap(), 59);
     assert_eq!(validators::unsigned_number_validator("0".to_string()).is_ok(), true);
-    assert_eq!(parsers::force_parse_integer(Some("0")), 0);
+    assert_eq!(parsers::force_parse_integer(Some("0")).unwrap(), 0);
     assert_eq!(validators::unsigned_number_validator("5000".to_string()).is_ok(), true);
-    assert_eq!(parsers::force_parse_integer(Some("5000")), 5000);
+    assert_eq!(parsers::force_parse_integer(Some("5000")).unwrap(), 5000);
 }
 
 #[test]
@@ -48,6 +87,74 @@ fn test_bad_brake_time(){
 
 #[test]
 fn test_date() {
-    assert_eq!(parsers::force_parse_datetime(Some("10:11"), Some("2019-11-10")),
+    assert_eq!(parsers::force_parse_datetime(Some("10:11"), Some("2019-11-10"), &clock()).unwrap(),
                Local.ymd(2019, 11, 10).and_hms(10, 11, 0));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_invalid_date_is_an_error() {
+    assert!(validators::day_validator("2019-02-30".to_string()).is_ok());
+    let error = parsers::get_date_from_string("2019-02-30", &clock()).unwrap_err();
+    assert_eq!(error.to_string(), "Specified value '2019-02-30' is not a valid date.");
+    assert_eq!(error.exit_code(), 2);
+    assert!(parsers::get_date_from_string("tomorrow", &clock()).is_err());
+    assert!(parsers::get_date_from_string("99999999999d", &clock()).is_err());
+}
+
+#[test]
+fn test_invalid_time_is_an_error() {
+    assert_eq!(parsers::force_parse_time("23:59".to_string()).unwrap(), (23, 59));
+    assert!(parsers::force_parse_time("24:00".to_string()).is_err());
+    assert!(parsers::force_parse_time("10:60".to_string()).is_err());
+    assert!(parsers::parse_time(None).unwrap().is_none());
+}
+
+#[test]
+fn test_12_hour_time() {
+    assert_eq!(parsers::force_parse_time("9:30am".to_string()).unwrap(), (9, 30));
+    assert_eq!(parsers::force_parse_time("5pm".to_string()).unwrap(), (17, 0));
+    assert_eq!(parsers::force_parse_time("5:15 PM".to_string()).unwrap(), (17, 15));
+    assert_eq!(parsers::force_parse_time("12am".to_string()).unwrap(), (0, 0));
+    assert_eq!(parsers::force_parse_time("12:45pm".to_string()).unwrap(), (12, 45));
+    assert_eq!(parsers::force_parse_time("9:05".to_string()).unwrap(), (9, 5));
+    assert!(validators::time_validator("9:30am".to_string()).is_ok());
+    assert!(validators::time_validator("17:00".to_string()).is_ok());
+    assert!(validators::time_validator("13pm".to_string()).is_err());
+    assert!(validators::time_validator("0am".to_string()).is_err());
+    assert!(validators::time_validator("24:00".to_string()).is_err());
+    assert!(parsers::force_parse_time("5:60pm".to_string()).is_err());
+}
+
+#[test]
+fn test_duration() {
+    assert_eq!(parsers::force_parse_duration(Some("450")).unwrap(), 450);
+    assert_eq!(parsers::force_parse_duration(Some("7h30m")).unwrap(), 450);
+    assert_eq!(parsers::force_parse_duration(Some("7.5h")).unwrap(), 450);
+    assert_eq!(parsers::force_parse_duration(Some("1:15")).unwrap(), 75);
+    assert_eq!(parsers::force_parse_duration(Some("-2h")).unwrap(), -120);
+    assert_eq!(parsers::force_parse_duration(Some("45m")).unwrap(), 45);
+    assert_eq!(parsers::force_parse_duration(Some("-30")).unwrap(), -30);
+    assert!(parsers::parse_duration(None).unwrap().is_none());
+    assert!(validators::unsigned_duration_validator("1h".to_string()).is_ok());
+    assert!(validators::unsigned_duration_validator("-1h".to_string()).is_err());
+    for invalid in &["", "-", "h", "1:75", "7h30", "1.5", "2 h", "99999999999h"] {
+        assert!(parsers::parse_duration(Some(invalid)).is_err(), "{} should be invalid", invalid);
+    }
+}
+
+#[test]
+fn test_too_large_integer_is_an_error() {
+    assert!(parsers::force_parse_integer(Some("99999999999")).is_err());
+}
+
+#[test]
+fn test_parse_clock() {
+    assert_eq!(Clock::parse("2019-11-12 10:30").unwrap().now(), Local.ymd(2019, 11, 12).and_hms(10, 30, 0));
+    assert_eq!(Clock::parse("2019-11-12").unwrap().now(), Local.ymd(2019, 11, 12).and_hms(0, 0, 0));
+    assert_eq!(Clock::parse("2019-11-12T10:30:00+00:00").unwrap().now().timestamp(), 1573554600);
+    match Clock::parse("").unwrap() {
+        Clock::System => (),
+        clock => panic!("Expected the system clock, got {:?}", clock)
+    }
+    assert_eq!(Clock::parse("yesterday").unwrap_err().exit_code(), 2);
+}
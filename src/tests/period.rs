@@ -0,0 +1,48 @@
+use crate::clock::Clock;
+use crate::period::*;
+use chrono::{Local, NaiveDate, TimeZone};
+
+fn clock() -> Clock {
+    Clock::Fixed(Local.ymd(2019, 11, 12).and_hms(10, 30, 0))
+}
+
+fn dates(period: &str) -> (NaiveDate, NaiveDate) {
+    let (start, end) = parse(period, &clock()).unwrap();
+    (start.naive_local().date(), end.naive_local().date())
+}
+
+#[test]
+fn test_plus_months() {
+    let date = |year, month, day| NaiveDate::from_ymd(year, month, day);
+    assert_eq!(plus_months(date(2019, 12, 1), 1), Some(date(2020, 1, 1)));
+    assert_eq!(plus_months(date(2020, 1, 31), 1), Some(date(2020, 2, 29)));
+    assert_eq!(plus_months(date(2020, 3, 15), -3), Some(date(2019, 12, 15)));
+    assert_eq!(plus_months(date(2019, 5, 1), 12), Some(date(2020, 5, 1)));
+}
+
+#[test]
+fn test_named_periods() {
+    let date = |year, month, day| NaiveDate::from_ymd(year, month, day);
+    assert_eq!(dates("this-week"), (date(2019, 11, 11), date(2019, 11, 18)));
+    assert_eq!(dates("last-week"), (date(2019, 11, 4), date(2019, 11, 11)));
+    assert_eq!(dates("this-month"), (date(2019, 11, 1), date(2019, 12, 1)));
+    assert_eq!(dates("last-month"), (date(2019, 10, 1), date(2019, 11, 1)));
+    assert_eq!(dates("last-year"), (date(2018, 1, 1), date(2019, 1, 1)));
+    assert_eq!(dates("ytd"), (date(2019, 1, 1), date(2019, 11, 13)));
+    assert_eq!(dates("2024"), (date(2024, 1, 1), date(2025, 1, 1)));
+    assert_eq!(dates("2024-03"), (date(2024, 3, 1), date(2024, 4, 1)));
+    assert_eq!(dates("2024-12"), (date(2024, 12, 1), date(2025, 1, 1)));
+    assert_eq!(dates("2024-W12"), (date(2024, 3, 18), date(2024, 3, 25)));
+    assert_eq!(dates("2024-Q2"), (date(2024, 4, 1), date(2024, 7, 1)));
+    assert_eq!(current_month(&clock()).unwrap(), parse("this-month", &clock()).unwrap());
+    assert_eq!(current_year(&clock()).unwrap(), parse("this-year", &clock()).unwrap());
+}
+
+#[test]
+fn test_invalid_period() {
+    assert!(validator("next-week".to_string()).is_err());
+    assert!(validator("2024-Q5".to_string()).is_err());
+    assert!(validator("24-03".to_string()).is_err());
+    assert_eq!(parse("2024-13", &clock()).unwrap_err().exit_code(), 2);
+    assert_eq!(parse("2019-W53", &clock()).unwrap_err().exit_code(), 2);
+}
@@ -0,0 +1,56 @@
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::db::storage::Storage;
+use crate::error::NpttError;
+use crate::profiles::*;
+use chrono::{Local, TimeZone};
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("nptt-profiles-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&path);
+    path
+}
+
+fn profiles(dir: &Path) -> Profiles {
+    Profiles::new(dir.join("db"), None, Some(dir.join("config")), Clock::Fixed(Local.ymd(2019, 11, 20).and_hms(12, 0, 0)))
+}
+
+#[test]
+fn test_create_list_and_switch() {
+    let dir = temp_dir("create");
+    let profiles = profiles(&dir);
+    assert_eq!(profiles.list().unwrap(), vec![DEFAULT_PROFILE.to_string()]);
+    assert_eq!(profiles.current().unwrap(), DEFAULT_PROFILE);
+    assert!(matches!(profiles.open("work"), Err(NpttError::Validation(_))));
+    assert!(matches!(profiles.switch("work"), Err(NpttError::Validation(_))));
+    profiles.create("work").unwrap();
+    profiles.create("consulting").unwrap();
+    assert!(matches!(profiles.create("work"), Err(NpttError::Validation(_))));
+    assert!(matches!(profiles.create(DEFAULT_PROFILE), Err(NpttError::Validation(_))));
+    assert!(matches!(profiles.create("../work"), Err(NpttError::Validation(_))));
+    assert_eq!(profiles.list().unwrap(), vec!["default", "consulting", "work"]);
+    profiles.switch("work").unwrap();
+    assert_eq!(profiles.current().unwrap(), "work");
+    assert_eq!(profiles.db_path("work"), dir.join("db").join(".nptt-profiles").join("work.db"));
+    assert_eq!(profiles.db_path(DEFAULT_PROFILE), dir.join("db").join(".nptt-db"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_profiles_have_separate_entries_and_config() {
+    let dir = temp_dir("separate");
+    let profiles = profiles(&dir);
+    profiles.create("work").unwrap();
+    let day = Local.ymd(2019, 11, 18);
+    profiles.open("work").unwrap().insert_time(&day.and_hms(8, 0, 0), &day.and_hms(12, 0, 0), 0).unwrap();
+    assert_eq!(profiles.open("work").unwrap().list_all_times().unwrap().len(), 1);
+    assert!(profiles.open(DEFAULT_PROFILE).unwrap().list_all_times().unwrap().is_empty());
+    assert_eq!(profiles.config("work").unwrap(), Config::default());
+    fs::create_dir_all(dir.join("config")).unwrap();
+    fs::write(profiles.config_path("work").unwrap(), "daily_minutes = 240").unwrap();
+    assert_eq!(profiles.config("work").unwrap().daily_minutes, 240);
+    assert_eq!(profiles.config(DEFAULT_PROFILE).unwrap().daily_minutes, 480);
+    fs::remove_dir_all(&dir).unwrap();
+}
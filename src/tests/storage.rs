@@ -0,0 +1,77 @@
+use crate::clock::Clock;
+use crate::db::db_manager::*;
+use crate::db::memory_storage::MemoryStorage;
+use crate::db::storage::Storage;
+use chrono::{Local, NaiveDate, TimeZone};
+use rusqlite::Error;
+
+fn clock() -> Clock {
+    Clock::Fixed(Local.ymd(2019, 11, 20).and_hms(12, 0, 0))
+}
+
+fn sqlite() -> DbConnection {
+    let connection = create_connection(":memory:", clock()).unwrap();
+    connection.create_tables().unwrap();
+    connection
+}
+
+/// Runs the same operations against a storage and describes everything that can be read back, so that the storages
+/// can be compared.
+fn describe(storage: &dyn Storage) -> Vec<String> {
+    let day = Local.ymd(2019, 11, 18);
+    let (from, to) = (Local.ymd(2019, 11, 1).and_hms(0, 0, 0), Local.ymd(2019, 12, 1).and_hms(0, 0, 0));
+    storage.insert_time(&day.and_hms(8, 0, 0), &day.and_hms(17, 0, 0), 60).unwrap();
+    storage.insert_time(&day.pred().and_hms(9, 0, 0), &day.pred().and_hms(16, 0, 0), 30).unwrap();
+    storage.insert_time(&Local.ymd(2019, 11, 25).and_hms(9, 0, 0), &Local.ymd(2019, 11, 25).and_hms(16, 0, 0), 0).unwrap();
+    storage.update_time(2, &day.pred().and_hms(9, 30, 0), &day.pred().and_hms(16, 0, 0), 45).unwrap();
+    storage.add_flex(30, &day, Some("Overtime")).unwrap();
+    storage.add_flex(-15, &day.pred(), None).unwrap();
+    storage.add_daily_time_override(&day, None, &[480, 480, 480, 480, 240, 0, 0]).unwrap();
+    storage.stop_daily_time_override(1, &day.succ()).unwrap();
+    storage.add_absence(AbsenceCategory::Vacation, &day.succ(), &day.succ().succ(), None, Some("Skiing")).unwrap();
+    storage.set_absence_allowance(AbsenceCategory::Vacation, 2019, 25.0).unwrap();
+    storage.set_absence_allowance(AbsenceCategory::Vacation, 2019, 27.5).unwrap();
+    storage.set_absence_allowance(AbsenceCategory::Other, 2019, 2.0).unwrap();
+    assert_eq!(storage.add_holidays(&[(NaiveDate::from_ymd(2019, 11, 1), "All Saints".to_string()), (NaiveDate::from_ymd(2019, 11, 1), "Again".to_string())]).unwrap(), 1);
+    storage.start_timer(&Local.ymd(2019, 11, 20).and_hms(8, 0, 0)).unwrap();
+
+    let mut lines = Vec::new();
+    for line in storage.list_times(&from, &to).unwrap() {
+        lines.push(format!("time {} {} {} {} {}", line.id, line.date, line.start, line.end, line.break_time_minutes));
+    }
+    for line in storage.list_flex(&from, &to).unwrap() {
+        lines.push(format!("flex {} {} {} '{}'", line.id, line.date, line.flex_minutes, line.comment));
+    }
+    for line in storage.list_daily_time_overrides().unwrap() {
+        lines.push(format!("override {} {} {:?} {:?}", line.id, line.start, line.end, line.minutes_per_weekday));
+    }
+    for line in storage.list_absences(&from, &to).unwrap() {
+        lines.push(format!("absence {} {} {} {} {:?} '{}'", line.id, line.category.name(), line.start, line.end, line.minutes_per_day, line.comment));
+    }
+    for line in storage.list_absence_allowances(2019).unwrap() {
+        lines.push(format!("allowance {} {} {}", line.category.name(), line.year, line.days));
+    }
+    for line in storage.list_holidays(&from, &to).unwrap() {
+        lines.push(format!("holiday {} {}", line.date, line.name));
+    }
+    lines.push(format!("entries {} last {}", storage.get_num_time_entries().unwrap(), storage.get_date_for_last_entry().unwrap()));
+    lines.push(format!("stopped timer started at {}", storage.stop_timer(&Local.ymd(2019, 11, 20).and_hms(11, 0, 0), 0).unwrap()));
+    lines.push(format!("timer {:?} times {}", storage.get_running_timer().unwrap(), storage.list_all_times().unwrap().len()));
+    lines.push(format!("rolled back {:?}", storage.in_transaction(|db| {
+        db.delete_time(1)?;
+        db.delete_flex(1)?;
+        db.delete_time(1)
+    }).is_err()));
+    lines.push(format!("times {} flex {}", storage.list_all_times().unwrap().len(), storage.list_all_flex().unwrap().len()));
+    assert!(matches!(storage.get_absence(2), Err(Error::QueryReturnedNoRows)));
+    assert!(matches!(storage.delete_holiday(&day), Err(Error::QueryReturnedNoRows)));
+    storage.delete_all_data().unwrap();
+    storage.insert_time(&day.and_hms(8, 0, 0), &day.and_hms(17, 0, 0), 60).unwrap();
+    lines.push(format!("id after delete {}", storage.list_all_times().unwrap()[0].id));
+    lines
+}
+
+#[test]
+fn test_memory_storage_behaves_like_sqlite() {
+    assert_eq!(describe(&MemoryStorage::new(clock())), describe(&sqlite()));
+}
@@ -0,0 +1,77 @@
+use crate::db::db_manager::*;
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::error::NpttError;
+use crate::report_generation::TimeFormat;
+use crate::tracker::Tracker;
+use chrono::{Local, NaiveDate, TimeZone};
+
+fn create_db() -> DbConnection {
+    let connection = create_connection(":memory:", Clock::Fixed(Local.ymd(2019, 11, 20).and_hms(12, 0, 0))).unwrap();
+    connection.create_tables().unwrap();
+    connection
+}
+
+#[test]
+fn test_add_edit_and_list_time() {
+    let connection = create_db();
+    let tracker = Tracker::new(&connection, Config::default());
+    let day = Local.ymd(2019, 11, 18);
+    tracker.add_time(&day.and_hms(8, 0, 0), &day.and_hms(17, 0, 0), 60).unwrap();
+    let id = tracker.list_time(&day.and_hms(0, 0, 0), &day.succ().and_hms(0, 0, 0)).unwrap()[0].id;
+    let edited = tracker.edit_time(id, None, Some((18, 30)), None, Some(30)).unwrap();
+    assert_eq!(edited.start, day.and_hms(8, 0, 0));
+    assert_eq!(edited.end, day.and_hms(18, 30, 0));
+    let lines = tracker.list_time(&day.and_hms(0, 0, 0), &day.succ().and_hms(0, 0, 0)).unwrap();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].break_time_minutes, 30);
+    assert!(matches!(tracker.edit_time(id, None, None, None, None), Err(NpttError::Validation(_))));
+    assert!(matches!(tracker.get_time(id + 1), Err(NpttError::Validation(_))));
+}
+
+#[test]
+fn test_flex_balance_and_report_data() {
+    let connection = create_db();
+    let tracker = Tracker::new(&connection, Config::default());
+    let day = Local.ymd(2019, 11, 18);
+    tracker.add_time(&day.and_hms(8, 0, 0), &day.and_hms(17, 30, 0), 30).unwrap(); // +60
+    tracker.add_flex(-30, &day.succ(), Some("Dentist")).unwrap();
+    tracker.add_holiday(&day.succ(), "Some holiday").unwrap();
+    assert_eq!(tracker.flex_balance(&day.succ().succ().and_hms(0, 0, 0)).unwrap(), 0.5);
+    assert!(matches!(tracker.add_holiday(&day.succ(), "Again"), Err(NpttError::Validation(_))));
+    let data = tracker.report_data(&day.and_hms(0, 0, 0), &day.succ().succ().and_hms(0, 0, 0)).unwrap();
+    assert_eq!(data.time_rows.len(), 1);
+    assert_eq!(data.flex_rows.len(), 1);
+    assert_eq!(data.holidays.len(), 1);
+    assert_eq!(data.opening_flex_hours, 0.0);
+    assert_eq!(data.closing_flex_hours, 0.5);
+}
+
+#[test]
+fn test_timer() {
+    let connection = create_db();
+    let tracker = Tracker::new(&connection, Config::default());
+    assert!(tracker.timer_status(0).unwrap().is_none());
+    tracker.start_timer(&Local.ymd(2019, 11, 20).and_hms(8, 0, 0), TimeFormat::H24).unwrap();
+    assert!(tracker.start_timer(&Local.ymd(2019, 11, 20).and_hms(9, 0, 0), TimeFormat::H24).is_err());
+    let status = tracker.timer_status(30).unwrap().unwrap();
+    assert_eq!(status.flex_minutes, 4 * 60 - 30 - 8 * 60);
+    let start = tracker.stop_timer(&Local.ymd(2019, 11, 20).and_hms(12, 0, 0), 30, TimeFormat::H24).unwrap();
+    assert_eq!(start, Local.ymd(2019, 11, 20).and_hms(8, 0, 0));
+    assert!(tracker.running_timer().unwrap().is_none());
+}
+
+#[test]
+fn test_config_schedule() {
+    let connection = create_db();
+    let config = Config::parse("daily_minutes = 450\nwork_days = [\"mon\", \"tue\", \"wed\", \"thu\", \"sat\"]").unwrap();
+    let tracker = Tracker::new(&connection, config);
+    assert_eq!(tracker.expected_minutes(&NaiveDate::from_ymd(2019, 11, 14)).unwrap(), 450);
+    assert_eq!(tracker.expected_minutes(&NaiveDate::from_ymd(2019, 11, 15)).unwrap(), 0);
+    assert_eq!(tracker.expected_minutes(&NaiveDate::from_ymd(2019, 11, 16)).unwrap(), 450);
+    let day = Local.ymd(2019, 11, 14);
+    tracker.add_time(&day.and_hms(8, 0, 0), &day.and_hms(16, 0, 0), 30).unwrap();
+    tracker.add_daily_time_override(&Local.ymd(2019, 11, 15), &[480, 480, 480, 480, 480, 0, 0]).unwrap();
+    assert_eq!(tracker.expected_minutes(&NaiveDate::from_ymd(2019, 11, 15)).unwrap(), 480);
+    assert_eq!(tracker.flex_balance(&day.succ().and_hms(0, 0, 0)).unwrap(), 0.0);
+}
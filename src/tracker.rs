@@ -0,0 +1,363 @@
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::csv_import::ImportEntry;
+use crate::db::db_manager::{DateLine, FlexLine, AbsenceLine, AbsenceCategory, HolidayLine, DailyTimeOverrideLine};
+use crate::db::storage::Storage;
+use crate::dump::{self, Dump, ImportCount, ImportMode};
+use crate::error::NpttError;
+use crate::flex::{calculate_flex, calculate_flex_hours, get_minutes_for_date, list_entries_for_days, list_times_for_days};
+use crate::holidays;
+use crate::report_generation::{ReportData, TimeFormat};
+use chrono::{DateTime, Local, Date, NaiveDate, TimeZone, Datelike, Timelike, Duration};
+use rusqlite::Error;
+
+/// The typed API over the time tracking data. The CLI is a thin layer over this: it parses arguments, calls a method
+/// and formats the result. Periods are from `start` (inclusive) to `end` (exclusive). The expected work on days without a
+/// daily time override comes from the `Config`.
+pub struct Tracker<'a> {
+    storage: &'a dyn Storage,
+    config: Config
+}
+
+/// A running timer and what stopping it now would give.
+pub struct TimerStatus {
+    pub start: DateTime<Local>,
+    pub now: DateTime<Local>,
+    /// The flex of the day the timer was started if it was stopped now.
+    pub flex_minutes: i64
+}
+
+/// When to leave today to reach a flex goal.
+pub struct LeaveTimes {
+    pub now: DateTime<Local>,
+    /// Leaving at this time gives 0 flex for today.
+    pub zero_flex_today: DateTime<Local>,
+    /// Leaving at this time gives the target flex balance.
+    pub target_balance: DateTime<Local>
+}
+
+impl<'a> Tracker<'a> {
+    pub fn new(storage: &'a dyn Storage, config: Config) -> Tracker<'a> {
+        Tracker { storage, config }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn clock(&self) -> &Clock {
+        self.storage.clock()
+    }
+
+    /// The current time without seconds, as time lines are reported with minute precision.
+    pub fn current_minute(&self) -> DateTime<Local> {
+        let now = self.clock().now();
+        now.with_second(0).and_then(|now| now.with_nanosecond(0)).unwrap_or(now)
+    }
+
+    /// Adds a time line. The end must be after the start and at most 24 hours later.
+    pub fn add_time(&self, start: &DateTime<Local>, end: &DateTime<Local>, break_time_minutes: i32) -> Result<(), NpttError> {
+        check_time_line(start, end)?;
+        self.storage.insert_time(start, end, break_time_minutes).map_err(NpttError::db("Could not insert row"))
+    }
+
+    /// The time lines of the days of the period with the midnight attribution of the config, as they are stored.
+    pub fn list_time(&self, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<Vec<DateLine>, NpttError> {
+        list_entries_for_days(self.storage, self.config.midnight_attribution, start, end).map_err(NpttError::db("Could not retrieve lines"))
+    }
+
+    /// The time lines that belong to the days of the period with the midnight attribution of the config, see
+    /// `attribute_to_days`.
+    pub fn time_for_days(&self, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<Vec<DateLine>, NpttError> {
+        list_times_for_days(self.storage, self.config.midnight_attribution, start, end).map_err(NpttError::db("Could not retrieve lines"))
+    }
+
+    pub fn get_time(&self, id: i32) -> Result<DateLine, NpttError> {
+        self.storage.get_time(id).map_err(NpttError::not_found("time line", id))
+    }
+
+    /// Changes the given parts of a time line and keeps the rest. An end before the start is on the day after. Returns
+    /// the updated line.
+    pub fn edit_time(&self, id: i32, start: Option<(u32, u32)>, end: Option<(u32, u32)>, day: Option<Date<Local>>, break_time_minutes: Option<i32>) -> Result<DateLine, NpttError> {
+        if start.is_none() && end.is_none() && day.is_none() && break_time_minutes.is_none() {
+            return Err(NpttError::Validation("Nothing to edit. Specify at least one of --start, --end, --day or -b.".to_string()));
+        }
+        let existing = self.get_time(id)?;
+        let day = day.unwrap_or_else(|| Local.ymd(existing.date.year(), existing.date.month(), existing.date.day()));
+        let (start_h, start_m) = start.unwrap_or((existing.start.hour(), existing.start.minute()));
+        let (end_h, end_m) = end.unwrap_or((existing.end.hour(), existing.end.minute()));
+        let new_start = day.and_hms(start_h, start_m, 0);
+        let mut new_end = day.and_hms(end_h, end_m, 0);
+        if new_end < new_start {
+            new_end = day.succ().and_hms(end_h, end_m, 0);
+        }
+        check_time_line(&new_start, &new_end)?;
+        let new_break_time = break_time_minutes.unwrap_or(existing.break_time_minutes);
+        self.storage.update_time(id, &new_start, &new_end, new_break_time)
+            .map_err(NpttError::db("Could not update time line"))?;
+        Ok(DateLine { id, start: new_start, end: new_end, break_time_minutes: new_break_time, date: day.naive_local() })
+    }
+
+    pub fn delete_time(&self, id: i32) -> Result<(), NpttError> {
+        self.storage.delete_time(id).map_err(NpttError::db("Could not delete time line"))
+    }
+
+    pub fn add_flex(&self, flex_minutes: i32, date: &Date<Local>, comment: Option<&str>) -> Result<(), NpttError> {
+        self.storage.add_flex(flex_minutes, date, comment).map_err(NpttError::db("Could not insert flex entry"))
+    }
+
+    pub fn list_flex(&self, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<Vec<FlexLine>, NpttError> {
+        self.storage.list_flex(start, end).map_err(NpttError::db("Could not retrieve lines"))
+    }
+
+    pub fn get_flex(&self, id: i32) -> Result<FlexLine, NpttError> {
+        self.storage.get_flex(id).map_err(NpttError::not_found("flex line", id))
+    }
+
+    pub fn delete_flex(&self, id: i32) -> Result<(), NpttError> {
+        self.storage.delete_flex(id).map_err(NpttError::db("Could not delete flex line"))
+    }
+
+    /// The flex balance in hours at `until` (exclusive).
+    pub fn flex_balance(&self, until: &DateTime<Local>) -> Result<f64, NpttError> {
+        calculate_flex_hours(self.storage, &self.config.default_schedule(), self.config.midnight_attribution, until).map_err(NpttError::db("Could not calculate flex time"))
+    }
+
+    pub fn add_absence(&self, category: AbsenceCategory, start: &Date<Local>, end: &Date<Local>, minutes_per_day: Option<i32>, comment: Option<&str>) -> Result<(), NpttError> {
+        if end < start {
+            return Err(NpttError::Validation("The end date of the absence cannot be before the start date.".to_string()));
+        }
+        self.storage.add_absence(category, start, end, minutes_per_day, comment).map_err(NpttError::db("Could not insert absence"))
+    }
+
+    pub fn list_absences(&self, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<Vec<AbsenceLine>, NpttError> {
+        self.storage.list_absences(start, end).map_err(NpttError::db("Could not retrieve absence"))
+    }
+
+    pub fn get_absence(&self, id: i32) -> Result<AbsenceLine, NpttError> {
+        self.storage.get_absence(id).map_err(NpttError::not_found("absence", id))
+    }
+
+    pub fn delete_absence(&self, id: i32) -> Result<(), NpttError> {
+        self.storage.delete_absence(id).map_err(NpttError::db("Could not delete absence"))
+    }
+
+    pub fn set_absence_allowance(&self, category: AbsenceCategory, year: i32, days: f64) -> Result<(), NpttError> {
+        self.storage.set_absence_allowance(category, year, days).map_err(NpttError::db("Could not set absence allowance"))
+    }
+
+    /// Adds the holidays for the dates that do not already have one. Returns the number of added holidays.
+    pub fn add_holidays(&self, holidays: &[(NaiveDate, String)]) -> Result<usize, NpttError> {
+        self.storage.add_holidays(holidays).map_err(NpttError::db("Could not insert holidays"))
+    }
+
+    /// Adds the built in holidays of a country for a year. Returns the number of added holidays and the number of holidays of the year.
+    pub fn import_holidays_for_country(&self, country: &str, year: i32) -> Result<(usize, usize), NpttError> {
+        let holidays = holidays::holidays_for_country(country, year).map_err(NpttError::Validation)?;
+        Ok((self.add_holidays(&holidays)?, holidays.len()))
+    }
+
+    pub fn add_holiday(&self, date: &Date<Local>, name: &str) -> Result<(), NpttError> {
+        if self.add_holidays(&[(date.naive_local(), name.to_string())])? == 0 {
+            return Err(NpttError::Validation(format!("There already is a holiday at {}.", date.format("%Y-%m-%d"))));
+        }
+        Ok(())
+    }
+
+    pub fn list_holidays(&self, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<Vec<HolidayLine>, NpttError> {
+        self.storage.list_holidays(start, end).map_err(NpttError::db("Could not retrieve holidays"))
+    }
+
+    pub fn delete_holiday(&self, date: &Date<Local>) -> Result<(), NpttError> {
+        self.storage.delete_holiday(date).map_err(|err| match err {
+            Error::QueryReturnedNoRows => NpttError::Validation(format!("There is no holiday at {}.", date.format("%Y-%m-%d"))),
+            _ => NpttError::Db("Could not delete holiday".to_string(), err)
+        })
+    }
+
+    /// Adds a schedule from `start` without an end. The minutes start with Monday.
+    pub fn add_daily_time_override(&self, start: &Date<Local>, minutes_per_weekday: &[i32; 7]) -> Result<(), NpttError> {
+        self.storage.add_daily_time_override(start, None, minutes_per_weekday).map_err(NpttError::db("Could not add daily time override"))
+    }
+
+    pub fn list_daily_time_overrides(&self) -> Result<Vec<DailyTimeOverrideLine>, NpttError> {
+        self.storage.list_daily_time_overrides().map_err(NpttError::db("Could not list time overrides"))
+    }
+
+    pub fn get_daily_time_override(&self, id: i32) -> Result<DailyTimeOverrideLine, NpttError> {
+        self.storage.get_daily_time_override(id).map_err(NpttError::not_found("daily time override", id))
+    }
+
+    pub fn stop_daily_time_override(&self, id: i32, end: &Date<Local>) -> Result<(), NpttError> {
+        self.storage.stop_daily_time_override(id, end).map_err(|err| match err {
+            Error::QueryReturnedNoRows => NpttError::Validation("Update matched no rows, did you specify the correct id?".to_string()),
+            _ => NpttError::Db("Could not update daily time override".to_string(), err)
+        })
+    }
+
+    pub fn delete_daily_time_override(&self, id: i32) -> Result<(), NpttError> {
+        self.storage.delete_daily_time_override(id).map_err(NpttError::db("Could not delete daily time override"))
+    }
+
+    pub fn running_timer(&self) -> Result<Option<DateTime<Local>>, NpttError> {
+        self.storage.get_running_timer().map_err(NpttError::db("Could not fetch running timer"))
+    }
+
+    pub fn start_timer(&self, start: &DateTime<Local>, time_format: TimeFormat) -> Result<(), NpttError> {
+        if let Some(running) = self.running_timer()? {
+            return Err(NpttError::Validation(format!("A timer has already been running since {}. Stop it with the stop command first.", running.format(&self.date_time_format(time_format)))));
+        }
+        self.storage.start_timer(start).map_err(NpttError::db("Could not start timer"))
+    }
+
+    /// Stops the running timer and adds it as a time line. Returns when the timer was started.
+    pub fn stop_timer(&self, end: &DateTime<Local>, break_time_minutes: i32, time_format: TimeFormat) -> Result<DateTime<Local>, NpttError> {
+        let start = self.running_timer()?
+            .ok_or_else(|| NpttError::Validation("No timer is running. Start one with the start command.".to_string()))?;
+        if *end <= start {
+            return Err(NpttError::Validation(format!("The timer cannot be stopped before it was started at {}.", start.format(&self.date_time_format(time_format)))));
+        }
+        self.storage.stop_timer(end, break_time_minutes).map_err(NpttError::db("Could not stop timer"))?;
+        Ok(start)
+    }
+
+    /// The date format of the config followed by `time_format`.
+    pub fn date_time_format(&self, time_format: TimeFormat) -> String {
+        format!("{} {}", self.config.date_format, time_format.strftime())
+    }
+
+    /// The running timer and the flex it would give if it was stopped now with the breaks. `None` if no timer is running.
+    pub fn timer_status(&self, break_time_minutes: i32) -> Result<Option<TimerStatus>, NpttError> {
+        let start = match self.running_timer()? {
+            None => return Ok(None),
+            Some(start) => start
+        };
+        let now = self.current_minute();
+        let date = start.naive_local().date();
+        let day_start = start.date().and_hms(0, 0, 0);
+        let day_end = start.date().succ().and_hms(0, 0, 0);
+        let mut rows = self.time_for_days(&day_start, &day_end)?;
+        rows.push(DateLine { id: 0, start, end: now, break_time_minutes, date });
+        let daily_time_overrides = self.list_daily_time_overrides()?;
+        let holidays = self.list_holidays(&day_start, &day_end)?;
+        let absences = self.list_absences(&day_start, &day_end)?;
+        let flex_minutes = calculate_flex(&date, &rows, &daily_time_overrides, &self.config.default_schedule(), &holidays, &absences);
+        Ok(Some(TimerStatus { start, now, flex_minutes }))
+    }
+
+    /// When to leave today, counting from the running timer, `start` or the end of the last time line today, in that order.
+    /// `expected_break` is the total break of the day; breaks already reported today are subtracted.
+    pub fn leave_at(&self, expected_break: i32, start: Option<DateTime<Local>>, target_flex_hours: f64) -> Result<LeaveTimes, NpttError> {
+        let now = self.current_minute();
+        let today = now.date();
+        let day_start = today.and_hms(0, 0, 0);
+        let day_end = today.succ().and_hms(0, 0, 0);
+        let mut rows = self.time_for_days(&day_start, &day_end)?;
+        let ongoing_start = self.running_timer()?.or(start)
+            .or_else(|| rows.iter().map(|row| row.end).max())
+            .ok_or_else(|| NpttError::Validation("Nothing is reported today. Start a timer or specify when you started with --start.".to_string()))?;
+        let reported_break: i32 = rows.iter().map(|row| row.break_time_minutes).sum();
+        rows.push(DateLine { id: 0, start: ongoing_start, end: ongoing_start, break_time_minutes: (expected_break - reported_break).max(0), date: today.naive_local() });
+        let daily_time_overrides = self.list_daily_time_overrides()?;
+        let holidays = self.list_holidays(&day_start, &day_end)?;
+        let absences = self.list_absences(&day_start, &day_end)?;
+        let flex_when_leaving_at_start = calculate_flex(&today.naive_local(), &rows, &daily_time_overrides, &self.config.default_schedule(), &holidays, &absences);
+        let flex_before_today = self.flex_balance(&day_start)?;
+        let flex_entries_today: i32 = self.list_flex(&day_start, &day_end)?.iter().map(|row| row.flex_minutes).sum();
+        let balance_before_today = (flex_before_today * 60.0).round() as i64 + flex_entries_today as i64;
+        Ok(LeaveTimes {
+            now,
+            zero_flex_today: ongoing_start + Duration::minutes(-flex_when_leaving_at_start),
+            target_balance: ongoing_start + Duration::minutes((target_flex_hours * 60.0).round() as i64 - balance_before_today - flex_when_leaving_at_start)
+        })
+    }
+
+    /// The days that smart-add asks for: the days after the last entry up to but not including today that have expected
+    /// work and no whole day absence.
+    pub fn dates_to_report(&self) -> Result<Vec<NaiveDate>, NpttError> {
+        let num_time_records = self.storage.get_num_time_entries().map_err(NpttError::db("Could not fetch existing time records"))?;
+        if num_time_records == 0 {
+            return Err(NpttError::Validation("You cannot use smart-add until you have at least one time entry. Add a record with the add-time command.".to_string()));
+        }
+        let mut last_entry = self.storage.get_date_for_last_entry().map_err(NpttError::db("Could not fetch time row"))?;
+        last_entry = last_entry.succ();
+        let mut dates_to_report = Vec::new();
+        let date: Date<Local> = self.clock().today();
+        let today = NaiveDate::from_ymd(date.year(), date.month(), date.day());
+        let first_date = Local.ymd(last_entry.year(), last_entry.month(), last_entry.day()).and_hms(0, 0, 0);
+        let absences = self.storage.list_absences(&first_date, &date.and_hms(0, 0, 0))
+            .map_err(NpttError::db("Could not fetch absences"))?;
+        let holidays = self.storage.list_holidays(&first_date, &date.and_hms(0, 0, 0))
+            .map_err(NpttError::db("Could not fetch holidays"))?;
+        let daily_time_overrides = self.storage.list_daily_time_overrides()
+            .map_err(NpttError::db("Could not fetch daily time overrides"))?;
+        while last_entry < today {
+            let absent_whole_day = absences.iter().any(|absence| absence.covers(&last_entry) && absence.minutes_per_day.is_none());
+            if get_minutes_for_date(&daily_time_overrides, &self.config.default_schedule(), &holidays, &last_entry) > 0 && !absent_whole_day {
+                dates_to_report.push(last_entry);
+            }
+            last_entry = last_entry.succ();
+        }
+        Ok(dates_to_report)
+    }
+
+    /// The expected minutes of work for a date, see `get_minutes_for_date`.
+    pub fn expected_minutes(&self, date: &NaiveDate) -> Result<i64, NpttError> {
+        let day = Local.ymd(date.year(), date.month(), date.day());
+        let daily_time_overrides = self.list_daily_time_overrides()?;
+        let holidays = self.list_holidays(&day.and_hms(0, 0, 0), &day.succ().and_hms(0, 0, 0))?;
+        Ok(get_minutes_for_date(&daily_time_overrides, &self.config.default_schedule(), &holidays, date))
+    }
+
+    /// Everything a report for the period needs. Absence and holidays are loaded from the start of the year of the
+    /// last day of the period so the absence balances cover the whole year.
+    pub fn report_data(&self, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<ReportData, NpttError> {
+        let absence_year = end.date().pred().year();
+        let year_start = (*start).min(Local.ymd(absence_year, 1, 1).and_hms(0, 0, 0));
+        Ok(ReportData {
+            time_rows: self.time_for_days(start, end)?,
+            flex_rows: self.list_flex(start, end)?,
+            daily_time_overrides: self.list_daily_time_overrides()?,
+            default_schedule: self.config.default_schedule(),
+            absences: self.list_absences(&year_start, end)?,
+            absence_allowances: self.storage.list_absence_allowances(absence_year).map_err(NpttError::db("Could not retrieve absence allowances"))?,
+            holidays: self.list_holidays(&year_start, end)?,
+            opening_flex_hours: self.flex_balance(start)?,
+            closing_flex_hours: self.flex_balance(end)?,
+            start: *start,
+            end: *end
+        })
+    }
+
+    /// Adds all entries in a single transaction, so nothing is added if one of them fails.
+    pub fn import_entries(&self, entries: &[ImportEntry]) -> Result<(), NpttError> {
+        self.storage.in_transaction(|db| {
+            for entry in entries {
+                match entry {
+                    ImportEntry::Time { start, end, break_time_minutes } => db.insert_time(start, end, *break_time_minutes)?,
+                    ImportEntry::Flex { date, minutes, comment } => db.add_flex(*minutes, date, comment.as_deref())?
+                }
+            }
+            Ok(())
+        }).map_err(NpttError::db("Could not import, nothing was imported"))
+    }
+
+    pub fn export(&self) -> Result<Dump, NpttError> {
+        dump::export(self.storage).map_err(NpttError::db("Could not export data"))
+    }
+
+    pub fn import(&self, dump: &Dump, mode: ImportMode) -> Result<Vec<ImportCount>, NpttError> {
+        dump::import(self.storage, dump, mode)
+    }
+}
+
+/// A time line must end after it starts and cannot be longer than 24 hours.
+pub(crate) fn check_time_line(start: &DateTime<Local>, end: &DateTime<Local>) -> Result<(), NpttError> {
+    if end <= start {
+        return Err(NpttError::Validation(format!("The end {} must be after the start {}.", end.format("%Y-%m-%d %H:%M"), start.format("%Y-%m-%d %H:%M"))));
+    }
+    if *end - *start > Duration::days(1) {
+        return Err(NpttError::Validation(format!("A time line cannot be longer than 24 hours, from {} to {}.", start.format("%Y-%m-%d %H:%M"), end.format("%Y-%m-%d %H:%M"))));
+    }
+    Ok(())
+}